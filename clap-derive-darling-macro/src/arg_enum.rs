@@ -5,26 +5,50 @@ use quote::quote;
 use syn::Ident;
 
 use crate::{
-    common::{ClapCommonIdents, ClapIdentName, ClapParserArgsCommon, ClapTokensResult},
+    common::{
+        ClapCommonIdents, ClapDocCommon, ClapDocCommonAuto, ClapDocHelpMarker, ClapIdentName,
+        ClapParserArgsCommon, ClapTokensResult, VecStringAttr,
+    },
     RenameAll, RenameAllCasing,
 };
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(clap), supports(enum_any))]
+#[darling(attributes(clap), supports(enum_any), forward_attrs(doc))]
 pub struct ClapArgEnum {
     ident: Ident,
+    attrs: Vec<syn::Attribute>,
     data: ast::Data<ClapArgEnumVariant, ()>,
 
     #[darling(default = "crate::default_rename_all")]
     rename_all: RenameAll,
+    #[darling(default)]
+    ignore_case: bool,
+    #[darling(default)]
+    infer: bool,
+    #[darling(default)]
+    from_str: bool,
+    #[darling(default)]
+    display: bool,
+    #[darling(default)]
+    variant_names: bool,
+    #[darling(default)]
+    remote: Option<syn::Path>,
 }
 
 impl ClapTokensResult for ClapArgEnum {
     fn to_tokens_result(&self) -> Result<proc_macro2::TokenStream> {
         let impl_arg_enum = self.to_tokens_impl_arg_enum()?;
+        let impl_from_str = self.to_tokens_impl_from_str();
+        let impl_display = self.to_tokens_impl_display()?;
+        let impl_variant_names = self.to_tokens_impl_variant_names()?;
+        let impl_remote = self.to_tokens_impl_remote()?;
 
         Ok(quote! {
             #impl_arg_enum
+            #impl_from_str
+            #impl_display
+            #impl_variant_names
+            #impl_remote
         })
     }
 }
@@ -41,6 +65,20 @@ impl ClapParserArgsCommon for ClapArgEnum {
         None
     }
 }
+impl ClapDocCommon for ClapArgEnum {
+    fn get_attrs(&self) -> Vec<syn::Attribute> {
+        self.attrs.clone()
+    }
+    fn get_help_about(&self) -> Option<String> {
+        None
+    }
+    fn get_long_help_about(&self) -> Option<String> {
+        None
+    }
+}
+impl ClapDocCommonAuto for ClapArgEnum {
+    type Marker = ClapDocHelpMarker;
+}
 
 impl ClapArgEnum {
     fn get_variants(&self) -> Vec<ClapArgEnumVariant> {
@@ -65,6 +103,7 @@ impl ClapArgEnum {
         let self_variants = self
             .get_variants()
             .iter()
+            .filter(|v| !v.skip && !v.other)
             .map(|v| {
                 let v_ident = &v.ident;
                 quote! { Self::#v_ident, }
@@ -77,6 +116,40 @@ impl ClapArgEnum {
             .map(|v| v.to_tokens_match_to_possible_value())
             .collect::<darling::Result<Vec<_>>>()?;
 
+        let docs = self.get_docs();
+        let long_help = if docs.is_empty() {
+            None
+        } else {
+            let docs = docs.join("\n");
+            Some(quote! {
+                fn long_help<'a>() -> Option<&'a str> {
+                    Some(#docs)
+                }
+            })
+        };
+
+        let other = self.to_tokens_other()?;
+
+        let ignore_case = if self.ignore_case {
+            Some(quote! {
+                fn ignore_case() -> bool {
+                    true
+                }
+            })
+        } else {
+            None
+        };
+
+        let infer = if self.infer {
+            Some(quote! {
+                fn infer() -> bool {
+                    true
+                }
+            })
+        } else {
+            None
+        };
+
         Ok(quote! {
             impl clap_derive_darling::ArgEnum for #ident {
                 fn value_variants<'a>() -> &'a [Self] {
@@ -87,22 +160,202 @@ impl ClapArgEnum {
                 fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
                     match self {
                         #(#match_to_possible_value)*
-                        _ => None,
                     }
                 }
+                #other
+                #ignore_case
+                #infer
+                #long_help
             }
         })
     }
+
+    fn get_other_variant(&self) -> darling::Result<Option<ClapArgEnumVariant>> {
+        let mut others = self.get_variants().into_iter().filter(|v| v.other);
+        let other = others.next();
+
+        if let Some(duplicate) = others.next() {
+            return Err(
+                darling::Error::custom("Only one variant can be marked as other")
+                    .with_span(&duplicate.ident),
+            );
+        }
+
+        Ok(other)
+    }
+
+    fn to_tokens_other(&self) -> darling::Result<Option<proc_macro2::TokenStream>> {
+        let other = match self.get_other_variant()? {
+            Some(other) => other,
+            None => return Ok(None),
+        };
+
+        if other.skip || !other.fields.is_newtype() {
+            return Err(darling::Error::custom(
+                "other should be used on a newtype variant which isn't skipped",
+            )
+            .with_span(&other.ident));
+        }
+
+        let other_ident = &other.ident;
+
+        Ok(Some(quote! {
+            fn from_other(input: &str) -> Option<Self> {
+                Some(Self::#other_ident(::std::convert::From::from(input)))
+            }
+            fn has_other() -> bool {
+                true
+            }
+        }))
+    }
+
+    fn to_tokens_impl_remote(&self) -> darling::Result<Option<proc_macro2::TokenStream>> {
+        let remote = match &self.remote {
+            Some(remote) => remote,
+            None => return Ok(None),
+        };
+
+        let ident = &self.ident;
+
+        let variants = self.get_variants();
+        if let Some(v) = variants.iter().find(|v| !v.fields.is_unit()) {
+            return Err(darling::Error::custom(
+                "Variants of a remote enum mirror should not have any fields",
+            )
+            .with_span(&v.ident));
+        }
+
+        let v_idents = variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+
+        Ok(Some(quote! {
+            impl ::std::convert::From<#ident> for #remote {
+                fn from(value: #ident) -> Self {
+                    match value {
+                        #(#ident::#v_idents => #remote::#v_idents,)*
+                    }
+                }
+            }
+            impl ::std::convert::TryFrom<#remote> for #ident {
+                type Error = #remote;
+
+                fn try_from(value: #remote) -> Result<Self, #remote> {
+                    // The remote enum may be non_exhaustive, or have variants the mirror leaves out
+                    #[allow(unreachable_patterns)]
+                    match value {
+                        #(#remote::#v_idents => Ok(#ident::#v_idents),)*
+                        value => Err(value),
+                    }
+                }
+            }
+        }))
+    }
+
+    fn to_tokens_impl_from_str(&self) -> Option<proc_macro2::TokenStream> {
+        if !self.from_str {
+            return None;
+        }
+
+        let ident = &self.ident;
+
+        Some(quote! {
+            impl ::std::str::FromStr for #ident {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    <Self as clap_derive_darling::ArgEnum>::from_str(
+                        s,
+                        <Self as clap_derive_darling::ArgEnum>::ignore_case(),
+                    )
+                }
+            }
+        })
+    }
+
+    fn to_tokens_impl_display(&self) -> darling::Result<Option<proc_macro2::TokenStream>> {
+        if !self.display {
+            return Ok(None);
+        }
+
+        let ident = &self.ident;
+
+        let match_names = self
+            .get_variants()
+            .iter()
+            .map(|v| {
+                let v_ident = &v.ident;
+                if v.other {
+                    return Ok(quote! {
+                        Self::#v_ident(other) => ::std::fmt::Display::fmt(other, f),
+                    });
+                }
+                if v.skip {
+                    return Err(darling::Error::custom(
+                        "display is not supported for enums with skipped variants",
+                    )
+                    .with_span(v_ident));
+                }
+                let name = v.get_name_or()?;
+                Ok(quote! {
+                    Self::#v_ident => f.write_str(#name),
+                })
+            })
+            .collect::<darling::Result<Vec<_>>>()?;
+
+        Ok(Some(quote! {
+            impl ::std::fmt::Display for #ident {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match self {
+                        #(#match_names)*
+                    }
+                }
+            }
+        }))
+    }
+
+    fn to_tokens_impl_variant_names(&self) -> darling::Result<Option<proc_macro2::TokenStream>> {
+        if !self.variant_names {
+            return Ok(None);
+        }
+
+        let ident = &self.ident;
+
+        let names = self
+            .get_variants()
+            .iter()
+            .filter(|v| !v.skip && !v.other)
+            .map(|v| v.get_name_or())
+            .collect::<darling::Result<Vec<_>>>()?;
+
+        Ok(Some(quote! {
+            impl #ident {
+                /// The CLI names of all possible values, in display order.
+                pub const VARIANT_NAMES: &'static [&'static str] = &[#(#names),*];
+            }
+        }))
+    }
 }
 
 #[derive(Debug, Clone, FromVariant)]
 #[darling(attributes(clap), forward_attrs(doc))]
 pub struct ClapArgEnumVariant {
     ident: Ident,
+    attrs: Vec<syn::Attribute>,
     fields: ast::Fields<()>,
 
     #[darling(default)]
     help: Option<String>,
+    #[darling(default)]
+    name: Option<String>,
+    #[darling(default, multiple)]
+    alias: Vec<String>,
+    #[darling(default)]
+    aliases: Option<VecStringAttr>,
+    #[darling(default)]
+    hide: bool,
+    #[darling(default)]
+    skip: bool,
+    #[darling(default)]
+    other: bool,
 
     #[darling(skip)]
     parent_ident: Option<Ident>,
@@ -121,6 +374,12 @@ impl ClapArgEnumVariant {
         let ident = &self.ident;
         let parent_ident = self.get_parent_ident()?;
 
+        if self.skip || self.other {
+            return Ok(quote! {
+                Self::#ident { .. } => None,
+            });
+        }
+
         if !self.fields.is_unit() {
             return Err(darling::Error::custom(format!(
                 "Enum variant {}::{} should not have any fields",
@@ -129,23 +388,54 @@ impl ClapArgEnumVariant {
             .with_span(&ident));
         }
 
-        let ident = &self.ident;
-        let name = self.get_name_or()?.to_rename_all_case(self.rename_all);
+        let name = self.get_name_or()?;
 
-        let help = self.help.as_ref().map(|v| quote! { .help(#v) });
+        // PossibleValue only has a single help line, so the long part of the docs is dropped
+        let help = self
+            .get_help_about()
+            .or_else(|| self.get_docs_short_long().0)
+            .map(|v| quote! { .help(#v) });
+        let aliases = self
+            .alias
+            .iter()
+            .cloned()
+            .chain(self.aliases.iter().flat_map(|v| v.to_strings()));
+        let hide = if self.hide {
+            Some(quote! { .hide(true) })
+        } else {
+            None
+        };
 
         Ok(quote! {
-            Self::#ident => Some(clap::PossibleValue::new(#name) #help),
+            Self::#ident => Some(clap::PossibleValue::new(#name) #help #(.alias(#aliases))* #hide),
         })
     }
 }
 
+impl ClapDocCommon for ClapArgEnumVariant {
+    fn get_attrs(&self) -> Vec<syn::Attribute> {
+        self.attrs.clone()
+    }
+    fn get_help_about(&self) -> Option<String> {
+        self.help.clone()
+    }
+    fn get_long_help_about(&self) -> Option<String> {
+        None
+    }
+}
+impl ClapDocCommonAuto for ClapArgEnumVariant {
+    type Marker = ClapDocHelpMarker;
+}
+
 impl ClapIdentName for ClapArgEnumVariant {
     fn get_ident(&self) -> Option<Ident> {
         Some(self.ident.clone())
     }
     fn get_name(&self) -> Option<String> {
-        let ident = &self.ident;
-        Some(quote!(#ident).to_string())
+        Some(
+            self.name
+                .clone()
+                .unwrap_or_else(|| self.ident.to_string().to_rename_all_case(self.rename_all)),
+        )
     }
 }
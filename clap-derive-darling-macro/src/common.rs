@@ -49,6 +49,9 @@ pub(crate) trait ClapCommonIdents {
     fn get_env_ident(&self) -> Ident {
         format_ident!("___env")
     }
+    fn get_flag_ident(&self) -> Ident {
+        format_ident!("___flag")
+    }
     fn get_app_ident(&self) -> Ident {
         format_ident!("___app")
     }
@@ -61,6 +64,7 @@ pub(crate) trait ClapCommonIdents {
 }
 
 pub(crate) trait ClapFieldParent: ClapIdentName + DynClone {
+    #[allow(dead_code)]
     fn get_ident_with_parent(&self) -> Result<Ident>;
 }
 
@@ -209,6 +213,15 @@ pub(crate) trait ClapTraitImpls:
         let augment_args_fields = self.to_tokens_augment_args_fields()?;
         let augment_args_for_update_fields = self.to_tokens_augment_args_for_update_fields()?;
 
+        let subcommand_path_fields = self
+            .get_fieldstructs()
+            .iter()
+            .map(|f| {
+                let ident = &f.ident;
+                f.to_tokens_subcommand_path(quote! { &self.#ident })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
         Ok(quote! {
             impl clap_derive_darling::Args for #ident {
                 fn augment_args<'a>(#app_ident: clap::App<'a>, #prefix_ident: Vec<&'static str>) -> clap::App<'a> {
@@ -229,6 +242,14 @@ pub(crate) trait ClapTraitImpls:
                         #author_and_version
                         #app_call_help_about
                 }
+                fn args_subcommand_path(&self) -> Vec<&str> {
+                    #[allow(unused_mut)]
+                    let mut clap_path = Vec::new();
+
+                    #(#subcommand_path_fields)*
+
+                    clap_path
+                }
             }
         })
     }
@@ -276,7 +297,11 @@ pub(crate) trait ClapTraitImpls:
                 }
             }
 
-            impl clap_derive_darling::Clap for #ident {}
+            impl clap_derive_darling::Clap for #ident {
+                fn subcommand_path(&self) -> Vec<&str> {
+                    <Self as clap_derive_darling::Args>::args_subcommand_path(self)
+                }
+            }
         })
     }
 }
@@ -442,7 +467,9 @@ impl ClapDocCommonAutoMarker for ClapDocAboutMarker {
     }
 }
 
+#[allow(dead_code)]
 pub(crate) trait ClapDocHelp: ClapDocCommonAuto<Marker = ClapDocHelpMarker> {}
+#[allow(dead_code)]
 pub(crate) trait ClapDocAbout: ClapDocCommonAuto<Marker = ClapDocAboutMarker> {}
 
 impl<T: ClapDocCommonAuto<Marker = ClapDocHelpMarker>> ClapDocHelp for T {}
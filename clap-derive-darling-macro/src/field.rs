@@ -8,7 +8,7 @@ use syn::{Expr, GenericArgument, Ident, LitStr, Path, PathArguments, Type};
 
 use crate::common::{
     ClapCommonIdents, ClapDocCommon, ClapDocCommonAuto, ClapDocHelpMarker, ClapFieldParent,
-    ClapIdentName, ClapTokensResult,
+    ClapIdentName, ClapTokensResult, VecStringAttr,
 };
 
 use super::{RenameAll, RenameAllCasing};
@@ -20,6 +20,8 @@ enum ClapArgType {
     T,
     VecT,
     OptionVecT,
+    BoxT,
+    OptionBoxT,
 }
 
 #[derive(Debug, Clone, FromField)]
@@ -56,14 +58,44 @@ pub(crate) struct ClapField {
     #[darling(default)]
     pub arg_enum: bool,
     #[darling(default)]
+    pub with: Option<Path>,
+    #[darling(default)]
     pub skip: Option<Override<Path>>,
     #[darling(default)]
     pub default_value: Option<String>,
+    #[darling(default)]
+    pub default_missing_value: Option<String>,
+    #[darling(default)]
+    pub secret: bool,
+    #[darling(default)]
+    pub range: Option<LitStr>,
+    #[darling(default)]
+    pub min_len: Option<usize>,
+    #[darling(default)]
+    pub max_len: Option<usize>,
+    #[darling(default)]
+    pub regex: Option<LitStr>,
+    #[darling(default)]
+    pub non_empty: bool,
+    #[darling(default)]
+    pub min_occurrences: Option<usize>,
+    #[darling(default)]
+    pub max_occurrences: Option<usize>,
+    #[darling(default)]
+    pub possible_values: Option<VecStringAttr>,
+    #[darling(default)]
+    pub ignore_case: bool,
+    #[darling(default)]
+    pub value_delimiter: Option<char>,
+    #[darling(default)]
+    pub pseudo_values: bool,
 
     #[darling(skip)]
     pub parent: Option<Box<dyn ClapFieldParent>>,
     #[darling(skip)]
     pub flatten_args: Vec<Vec<String>>,
+    #[darling(skip)]
+    pub env_scope: Option<String>,
 
     #[darling(skip, default = "crate::default_rename_all")]
     pub rename_all: RenameAll,
@@ -134,6 +166,17 @@ impl ClapFieldParse {
             _ => return Err(Error::unknown_value("Parse should have been defaulted...")),
         })
     }
+    /// The parse function wrapped to always return a `Result`
+    pub fn parse_fallible(&self) -> Result<TokenStream> {
+        let parsed = self.parse()?;
+
+        Ok(match self {
+            ClapFieldParse::FromStr(..) => quote! {
+                (|s: &str| -> Result<_, ::std::convert::Infallible> { Ok(#parsed(s)) })
+            },
+            _ => quote! { #parsed },
+        })
+    }
 }
 
 impl Default for ClapFieldParse {
@@ -172,6 +215,12 @@ impl ClapField {
                 quote!(core::vec::Vec),
                 quote!(vec::Vec),
                 quote!(Vec),
+                quote!(std::collections::BTreeSet),
+                quote!(std::collections::HashSet),
+                quote!(collections::BTreeSet),
+                quote!(collections::HashSet),
+                quote!(BTreeSet),
+                quote!(HashSet),
             ],
             level,
         )
@@ -218,6 +267,20 @@ impl ClapField {
         )
     }
 
+    fn types_without_generics_eq_set(&self, input: &OptionSynPath) -> OptionSynPath {
+        self.types_without_generics_eq(
+            input,
+            &[
+                quote!(std::collections::BTreeSet),
+                quote!(std::collections::HashSet),
+                quote!(collections::BTreeSet),
+                quote!(collections::HashSet),
+                quote!(BTreeSet),
+                quote!(HashSet),
+            ],
+        )
+    }
+
     fn types_without_generics_eq_option(&self, input: &OptionSynPath) -> OptionSynPath {
         self.types_without_generics_eq(
             input,
@@ -230,6 +293,25 @@ impl ClapField {
         )
     }
 
+    fn types_without_generics_eq_box(&self, input: &OptionSynPath) -> OptionSynPath {
+        self.types_without_generics_eq(
+            input,
+            &[
+                quote!(std::boxed::Box),
+                quote!(alloc::boxed::Box),
+                quote!(boxed::Box),
+                quote!(Box),
+            ],
+        )
+    }
+
+    fn types_without_generics_eq_secret(&self, input: &OptionSynPath) -> OptionSynPath {
+        self.types_without_generics_eq(
+            input,
+            &[quote!(clap_derive_darling::Secret), quote!(Secret)],
+        )
+    }
+
     fn types_without_generics_eq(
         &self,
         input: &OptionSynPath,
@@ -280,10 +362,14 @@ impl ClapField {
             Ok((ClapArgType::OptionT, stripped_type_path))
         } else if prefixes == ["Option", "Option"] {
             Ok((ClapArgType::OptionOptionT, stripped_type_path))
-        } else if prefixes == ["Vec"] {
+        } else if prefixes == ["Vec"] || prefixes == ["Set"] {
             Ok((ClapArgType::VecT, stripped_type_path))
-        } else if prefixes == ["Option", "Vec"] {
+        } else if prefixes == ["Option", "Vec"] || prefixes == ["Option", "Set"] {
             Ok((ClapArgType::OptionVecT, stripped_type_path))
+        } else if prefixes == ["Box"] {
+            Ok((ClapArgType::BoxT, stripped_type_path))
+        } else if prefixes == ["Option", "Box"] {
+            Ok((ClapArgType::OptionBoxT, stripped_type_path))
         } else {
             Err(
                 Error::custom(format!("Type {:?} does not conform to standards", &self.ty))
@@ -301,9 +387,26 @@ impl ClapField {
             if self.types_without_generics_eq_vec(&ty).is_some() {
                 prefixes.push("Vec");
                 set = true;
+            } else if self.types_without_generics_eq_set(&ty).is_some() {
+                prefixes.push("Set");
+                set = true;
             } else if self.types_without_generics_eq_option(&ty).is_some() {
                 prefixes.push("Option");
                 set = true;
+            } else if self.subcommand && self.types_without_generics_eq_box(&ty).is_some() {
+                // Only subcommand fields may be boxed, to allow recursive enums
+                prefixes.push("Box");
+                ty = self.get_type_new_strip_types_impl(
+                    &ty,
+                    &[
+                        quote!(std::boxed::Box),
+                        quote!(alloc::boxed::Box),
+                        quote!(boxed::Box),
+                        quote!(Box),
+                    ],
+                    1,
+                );
+                continue;
             }
             if set {
                 ty = self.get_type_new_strip_vec_option(&ty, 1);
@@ -315,6 +418,55 @@ impl ClapField {
         (prefixes, ty)
     }
 
+    /// Whether a subcommand field is optional and boxed, along with the `Subcommand` type
+    fn get_subcommand_type(&self) -> Result<(bool, bool, TokenStream)> {
+        let ty = &self.ty;
+        let (arg_type, stripped_type_path) = self.get_arg_type()?;
+
+        Ok(match arg_type {
+            ClapArgType::T => (false, false, quote! { #ty }),
+            ClapArgType::OptionT => (true, false, quote! { #stripped_type_path }),
+            ClapArgType::BoxT => (false, true, quote! { #stripped_type_path }),
+            ClapArgType::OptionBoxT => (true, true, quote! { #stripped_type_path }),
+            _ => {
+                return Err(Error::unexpected_type(&ty.to_token_stream().to_string()).with_span(ty))
+            }
+        })
+    }
+
+    pub fn get_type_name(&self) -> Result<Option<String>> {
+        let (_, stripped_type_path) = self.get_arg_type()?;
+
+        Ok(stripped_type_path.and_then(|path| path.last().map(|seg| seg.ident.to_string())))
+    }
+
+    fn is_secret(&self) -> Result<bool> {
+        let (_, stripped_type_path) = self.get_arg_type()?;
+
+        Ok(self.secret
+            || self
+                .types_without_generics_eq_secret(&stripped_type_path)
+                .is_some())
+    }
+
+    fn get_short(&self) -> Result<Option<char>> {
+        Ok(match &self.short {
+            Some(Override::Explicit(short)) => Some(
+                short
+                    .chars()
+                    .next()
+                    .ok_or_else(|| Error::unknown_value(short))?,
+            ),
+            Some(Override::Inherit) => Some(
+                self.get_name_or()?
+                    .chars()
+                    .next()
+                    .ok_or_else(|| Error::custom("Could not build short value from field name"))?,
+            ),
+            None => None,
+        })
+    }
+
     fn get_parse_defaulted(&self) -> Result<ClapFieldParse> {
         let (arg_type, _) = self.get_arg_type()?;
 
@@ -356,14 +508,29 @@ impl ClapField {
         let prefix_ident = self.get_prefix_ident();
 
         Ok(if self.subcommand {
-            let ty = &self.ty;
-            if !matches!(arg_type, ClapArgType::T) {
-                return Err(Error::unexpected_type(&ty.to_token_stream().to_string()).with_span(ty));
-            }
+            let (optional, boxed, ty) = self.get_subcommand_type()?;
 
-            quote! {
-                let #app_ident = <#ty as clap_derive_darling::Subcommand>::augment_subcommands(#app_ident, #prefix_ident.clone());
-                let #app_ident = #app_ident.setting(clap::AppSettings::SubcommandRequiredElseHelp);
+            let augment = if boxed {
+                quote! {
+                    let #app_ident = clap_derive_darling::augment_boxed_subcommands(#app_ident);
+                }
+            } else {
+                quote! {
+                    let #app_ident = <#ty as clap_derive_darling::Subcommand>::augment_subcommands(#app_ident, #prefix_ident.clone());
+                }
+            };
+
+            if optional {
+                augment
+            } else {
+                quote! {
+                    #augment
+                    let #app_ident = if <#ty as clap_derive_darling::Subcommand>::has_default_subcommand() {
+                        #app_ident
+                    } else {
+                        #app_ident.setting(clap::AppSettings::SubcommandRequiredElseHelp)
+                    };
+                }
             }
         } else if self.skip.is_some() {
             quote! {}
@@ -383,7 +550,10 @@ impl ClapField {
         } else {
             let name = self.get_name_or()?;
             let parse = self.get_parse_defaulted()?;
-            let parse_expr = parse.parse()?;
+            let parse_expr = parse.parse_fallible()?;
+
+            self.check_multiple_values()?;
+            let arg_enum_type = self.get_arg_enum_type(&stripped_type_path)?;
 
             let name_ident = self.get_name_ident();
             let value_ident = self.get_value_ident();
@@ -397,26 +567,11 @@ impl ClapField {
                 clap::Arg::new(#name_ident)
             };
 
-            let builder = match &self.short {
-                Some(Override::Explicit(short)) => {
-                    let short = short
-                        .chars()
-                        .next()
-                        .ok_or_else(|| Error::unknown_value(short))?;
-                    quote! {
-                        #builder
-                            .short(#short)
-                    }
-                }
-                Some(Override::Inherit) => {
-                    let short = name.chars().next().ok_or_else(|| {
-                        Error::custom("Could not build short value from field name")
-                    })?;
-                    quote! {
-                        #builder
-                            .short(#short)
-                    }
-                }
+            let builder = match self.get_short()? {
+                Some(short) => quote! {
+                    #builder
+                        .short(#short)
+                },
                 None => builder,
             };
 
@@ -456,6 +611,16 @@ impl ClapField {
                 None => builder,
             };
 
+            let builder = if self.is_secret()? {
+                quote! {
+                    #builder
+                        .hide_env_values(true)
+                        .hide_default_value(true)
+                }
+            } else {
+                builder
+            };
+
             let builder = match &parse {
                 ClapFieldParse::FromFlag(..) => {
                     quote! {
@@ -497,11 +662,66 @@ impl ClapField {
                         builder
                     }
                 }
-                ClapArgType::VecT | ClapArgType::OptionVecT => quote! {
+                ClapArgType::BoxT | ClapArgType::OptionBoxT => {
+                    return Err(
+                        Error::unexpected_type(&self.ty.to_token_stream().to_string())
+                            .with_span(&self.ty),
+                    )
+                }
+                ClapArgType::VecT | ClapArgType::OptionVecT => {
+                    let required = self.min_occurrences.unwrap_or_default() > 0;
+                    let max_occurrences = self.max_occurrences.map(|max| {
+                        quote! {
+                            .max_occurrences(#max)
+                        }
+                    });
+
+                    let value_delimiter = self.value_delimiter.map(|delimiter| {
+                        quote! {
+                            .value_delimiter(#delimiter)
+                        }
+                    });
+
+                    // Option<Vec<T>> accepts the bare flag as an empty list of values, while
+                    // still taking at most one value per occurrence
+                    let min_values = if matches!(arg_type, ClapArgType::OptionVecT) {
+                        Some(quote! {
+                            .min_values(0)
+                            .number_of_values(1)
+                        })
+                    } else {
+                        None
+                    };
+
+                    quote! {
+                        #builder
+                            .required(#required)
+                            .multiple_occurrences(true)
+                            #max_occurrences
+                            #min_values
+                            #value_delimiter
+                    }
+                }
+            };
+
+            let builder = match (&self.default_missing_value, &arg_type) {
+                (None, _) => builder,
+                (Some(default_missing_value), ClapArgType::OptionOptionT) => quote! {
                     #builder
-                        .required(false)
-                        .multiple_occurrences(true)
+                        .default_missing_value(#default_missing_value)
                 },
+                (Some(default_missing_value), ClapArgType::T | ClapArgType::OptionT) => quote! {
+                    #builder
+                        .min_values(0)
+                        .max_values(1)
+                        .default_missing_value(#default_missing_value)
+                },
+                (Some(_), _) => {
+                    return Err(Error::custom(
+                        "default_missing_value requires a field with a single value",
+                    )
+                    .with_span(&self.ty))
+                }
             };
 
             let builder = if let Some(help) = self.to_tokens_app_call_help_about() {
@@ -513,17 +733,97 @@ impl ClapField {
                 builder
             };
 
-            let builder = if self.arg_enum {
+            let builder = if self.arg_enum
+                && self.get_long_help_about().is_none()
+                && self.get_docs_short_long().1.is_none()
+            {
+                let help = self
+                    .get_help_about()
+                    .or_else(|| self.get_docs_short_long().0);
+
+                match help {
+                    // --help only shows the long help, so the enum doc follows the field's own doc
+                    Some(help) => quote! {
+                        #builder
+                            .long_help({
+                                static LONG_HELP: clap_derive_darling::OnceBox<Option<String>> =
+                                    clap_derive_darling::OnceBox::new();
+
+                                LONG_HELP
+                                    .get_or_init(|| {
+                                        Box::new(
+                                            <#arg_enum_type as clap_derive_darling::ArgEnum>::long_help()
+                                                .map(|doc| format!("{}\n\n{}", #help, doc)),
+                                        )
+                                    })
+                                    .as_deref()
+                            })
+                    },
+                    None => quote! {
+                        #builder
+                            .long_help(<#arg_enum_type as clap_derive_darling::ArgEnum>::long_help())
+                    },
+                }
+            } else {
+                builder
+            };
+
+            let builder = if let Some(possible_values) = self.get_possible_values()? {
                 quote! {
                     #builder
-                        .possible_values(
-                            <#stripped_type_path as clap_derive_darling::ArgEnum>::value_variants()
-                                .iter()
-                                .filter_map(clap_derive_darling::ArgEnum::to_possible_value),
-                        )
+                        .possible_values([#(#possible_values),*])
+                }
+            } else {
+                builder
+            };
+
+            let builder = if self.ignore_case {
+                quote! {
+                    #builder
+                        .ignore_case(true)
+                }
+            } else if self.arg_enum {
+                quote! {
+                    #builder
+                        .ignore_case(<#arg_enum_type as clap_derive_darling::ArgEnum>::ignore_case())
+                }
+            } else {
+                builder
+            };
+
+            let pseudo_values = if self.pseudo_values {
+                quote! {
+                    vec![
+                        clap::PossibleValue::new("all").help("All values"),
+                        clap::PossibleValue::new("none").help("No values"),
+                    ]
+                }
+            } else {
+                quote! { Vec::new() }
+            };
+
+            let builder = if self.arg_enum {
+                quote! {
+                    clap_derive_darling::arg_enum_values::<#arg_enum_type>(
+                        #builder,
+                        #pseudo_values,
+                    )
                 }
             } else if matches!(arg_type, ClapArgType::Bool) {
                 builder
+            } else if let Some(validations) = self.to_tokens_validations()? {
+                quote! {
+                    #builder
+                        .validator(|s: &str| -> Result<(), String> {
+                            let value: Result<#stripped_type_path, _> = #parse_expr(s);
+                            #[allow(unused_variables)]
+                            let value = value.map_err(|err| err.to_string())?;
+
+                            #validations
+
+                            Ok(())
+                        })
+                }
             } else {
                 quote! {
                     #builder
@@ -543,6 +843,218 @@ impl ClapField {
         })
     }
 
+    /// The `ArgEnum` type of the field, which is a local mirror enum for remote types
+    fn get_arg_enum_type(&self, stripped_type_path: &OptionSynPath) -> Result<TokenStream> {
+        match &self.with {
+            Some(with) if self.arg_enum => Ok(quote! { #with }),
+            Some(with) => Err(Error::custom("with requires an arg_enum field").with_span(with)),
+            None => Ok(quote! { #stripped_type_path }),
+        }
+    }
+
+    fn check_multiple_values(&self) -> Result<()> {
+        let (arg_type, _) = self.get_arg_type()?;
+
+        if self.value_delimiter.is_some()
+            && !matches!(arg_type, ClapArgType::VecT | ClapArgType::OptionVecT)
+        {
+            return Err(
+                Error::custom("value_delimiter requires a Vec or set field").with_span(&self.ty)
+            );
+        }
+
+        if self.pseudo_values
+            && !(self.arg_enum && matches!(arg_type, ClapArgType::VecT | ClapArgType::OptionVecT))
+        {
+            return Err(
+                Error::custom("pseudo_values requires an arg_enum Vec or set field")
+                    .with_span(&self.ty),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn get_possible_values(&self) -> Result<Option<Vec<String>>> {
+        let (arg_type, _) = self.get_arg_type()?;
+
+        if self.ignore_case && !self.arg_enum && self.possible_values.is_none() {
+            return Err(
+                Error::custom("ignore_case requires possible_values or arg_enum")
+                    .with_span(&self.ty),
+            );
+        }
+
+        let possible_values = match &self.possible_values {
+            Some(possible_values) => possible_values.to_strings(),
+            None => return Ok(None),
+        };
+
+        if self.arg_enum || matches!(arg_type, ClapArgType::Bool) {
+            return Err(Error::custom(
+                "possible_values is not supported for flags and arg_enum fields",
+            )
+            .with_span(&self.ty));
+        }
+
+        Ok(Some(possible_values))
+    }
+
+    fn to_tokens_validations(&self) -> Result<Option<TokenStream>> {
+        let (arg_type, _) = self.get_arg_type()?;
+
+        let has_validations = self.range.is_some()
+            || self.min_len.is_some()
+            || self.max_len.is_some()
+            || self.regex.is_some()
+            || self.non_empty;
+
+        let has_occurrences = self.min_occurrences.is_some() || self.max_occurrences.is_some();
+
+        if has_occurrences && !matches!(arg_type, ClapArgType::VecT | ClapArgType::OptionVecT) {
+            return Err(
+                Error::custom("min_occurrences and max_occurrences require a Vec field")
+                    .with_span(&self.ty),
+            );
+        }
+
+        if !has_validations {
+            return Ok(None);
+        }
+
+        if self.arg_enum || matches!(arg_type, ClapArgType::Bool) {
+            return Err(Error::custom(
+                "Value validation is not supported for flags and arg_enum fields",
+            )
+            .with_span(&self.ty));
+        }
+
+        let range = self
+            .range
+            .as_ref()
+            .map(|range| -> Result<TokenStream> {
+                let range_str = range.value();
+                let range_expr: Expr = range.parse()?;
+                Ok(quote! {
+                    if !(#range_expr).contains(&value) {
+                        return Err(format!("value must be in the range {}", #range_str));
+                    }
+                })
+            })
+            .transpose()?;
+
+        let min_len = self.min_len.map(|min_len| {
+            quote! {
+                if s.chars().count() < #min_len {
+                    return Err(format!("value must be at least {} characters long", #min_len));
+                }
+            }
+        });
+
+        let max_len = self.max_len.map(|max_len| {
+            quote! {
+                if s.chars().count() > #max_len {
+                    return Err(format!("value must be at most {} characters long", #max_len));
+                }
+            }
+        });
+
+        let regex = self
+            .regex
+            .as_ref()
+            .map(|regex| -> Result<TokenStream> {
+                regex::Regex::new(&regex.value())
+                    .map_err(|err| Error::custom(err).with_span(regex))?;
+                Ok(quote! {
+                    static REGEX: clap_derive_darling::OnceBox<clap_derive_darling::regex::Regex> =
+                        clap_derive_darling::OnceBox::new();
+                    let regex = REGEX.get_or_init(|| {
+                        Box::new(
+                            clap_derive_darling::regex::Regex::new(#regex)
+                                .expect("regex was checked by the derive"),
+                        )
+                    });
+                    if !regex.is_match(s) {
+                        return Err(format!("value must match the pattern {}", #regex));
+                    }
+                })
+            })
+            .transpose()?;
+
+        let non_empty = if self.non_empty {
+            Some(quote! {
+                if s.is_empty() {
+                    return Err(String::from("value must not be empty"));
+                }
+            })
+        } else {
+            None
+        };
+
+        Ok(Some(quote! {
+            #non_empty
+            #min_len
+            #max_len
+            #regex
+            #range
+        }))
+    }
+
+    fn to_tokens_occurrences(&self) -> Option<TokenStream> {
+        let arg_matches_ident = self.get_arg_matches_ident();
+        let name_ident = self.get_name_ident();
+        let flag_ident = self.get_flag_ident();
+
+        self.min_occurrences.map(|min| {
+            quote! {
+                let occurrences = #arg_matches_ident.occurrences_of(&#name_ident) as usize;
+                if occurrences < #min {
+                    return Err(clap::Error::raw(
+                        clap::ErrorKind::TooFewValues,
+                        format!("{} must be given at least {} times, but was given {} times", &#flag_ident, #min, occurrences)
+                    ));
+                }
+            }
+        })
+    }
+
+    fn to_tokens_flag(&self) -> Result<TokenStream> {
+        let flag_ident = self.get_flag_ident();
+        let name = self.get_name_or()?;
+
+        Ok(match (&self.long, self.get_short()?) {
+            (Some(long), _) => {
+                let long_ident = self.get_long_ident();
+                let long = match long {
+                    Override::Explicit(long) => long.clone(),
+                    Override::Inherit => name,
+                };
+                let required_ident = self.to_tokens_required_ident(&long_ident, &long)?;
+
+                quote! {
+                    let #flag_ident = {
+                        #required_ident
+                        format!("--{}", #long_ident)
+                    };
+                }
+            }
+            (None, Some(short)) => quote! {
+                let #flag_ident = format!("-{}", #short);
+            },
+            (None, None) => {
+                let value_ident = self.get_value_ident();
+                let required_ident = self.to_tokens_required_ident(&value_ident, &name)?;
+
+                quote! {
+                    let #flag_ident = {
+                        #required_ident
+                        format!("<{}>", #value_ident)
+                    };
+                }
+            }
+        })
+    }
+
     fn to_tokens_required_idents(
         &self,
         required_idents: HashMap<&Ident, Option<String>>,
@@ -554,11 +1066,18 @@ impl ClapField {
         let long_ident = self.get_long_ident();
         let env_ident = self.get_env_ident();
 
-        [name_ident, value_ident, long_ident, env_ident]
+        // Only derived env names are scoped, explicit ones are left out of the scope
+        let env = match &self.env_scope {
+            Some(scope) => format!("{}_{}", scope, name),
+            None => name.clone(),
+        };
+
+        [name_ident, value_ident, long_ident, env_ident.clone()]
             .iter()
             .filter_map(|req_ident| {
                 required_idents.get(req_ident).map(|val| {
-                    self.to_tokens_required_ident(req_ident, val.as_ref().unwrap_or(&name))
+                    let default = if req_ident == &env_ident { &env } else { &name };
+                    self.to_tokens_required_ident(req_ident, val.as_ref().unwrap_or(default))
                 })
             })
             .collect()
@@ -639,6 +1158,10 @@ impl ClapField {
         })
     }
 
+    pub fn to_tokens_parse_value(&self) -> Result<TokenStream> {
+        self.to_tokens_parse(None)
+    }
+
     pub fn to_tokens_update_from_arg_matches(&self) -> Result<TokenStream> {
         let ident = &self.ident;
 
@@ -679,30 +1202,117 @@ impl ClapField {
         })
     }
 
-    fn to_tokens_parse(&self, update_ident: Option<Ident>) -> Result<TokenStream> {
+    /// Extend `clap_path` with the subcommands selected within `access`, a reference to this field
+    pub fn to_tokens_subcommand_path(&self, access: TokenStream) -> Result<TokenStream> {
         let ty = &self.ty;
+
+        Ok(if self.skip.is_some() {
+            quote! {}
+        } else if self.subcommand {
+            let (optional, _, ty) = self.get_subcommand_type()?;
+
+            if optional {
+                quote! {
+                    if let Some(clap_subcommand) = ::std::option::Option::as_ref(#access) {
+                        clap_path.extend(<#ty as clap_derive_darling::Subcommand>::subcommand_path(clap_subcommand));
+                    }
+                }
+            } else {
+                quote! {
+                    clap_path.extend(<#ty as clap_derive_darling::Subcommand>::subcommand_path(#access));
+                }
+            }
+        } else if self.flatten.is_some() {
+            quote! {
+                clap_path.extend(<#ty as clap_derive_darling::Args>::args_subcommand_path(#access));
+            }
+        } else {
+            quote! {}
+        })
+    }
+
+    fn to_tokens_parse(&self, update_ident: Option<Ident>) -> Result<TokenStream> {
         let arg_matches_ident = self.get_arg_matches_ident();
         let prefix_ident = self.get_prefix_ident();
 
         let (arg_type, stripped_type_path) = self.get_arg_type()?;
 
         Ok(if self.subcommand {
-            if !matches!(arg_type, ClapArgType::T) {
-                return Err(Error::unexpected_type(&ty.to_token_stream().to_string()).with_span(ty));
-            }
+            let (optional, boxed, ty) = self.get_subcommand_type()?;
 
-            if let Some(update_ident) = update_ident {
-                quote! {
+            let from_arg_matches = quote! {
+                <#ty as clap_derive_darling::FromArgMatches>::from_arg_matches(#arg_matches_ident, #prefix_ident.clone())?
+            };
+            let from_arg_matches = if boxed {
+                quote! { Box::new(#from_arg_matches) }
+            } else {
+                from_arg_matches
+            };
+
+            let boxed_subcommand_matches = if update_ident.is_some() {
+                quote!(boxed_subcommand_matches_for_update)
+            } else {
+                quote!(boxed_subcommand_matches)
+            };
+
+            let parse = match (optional, update_ident) {
+                (false, Some(update_ident)) => quote! {
                     <#ty as clap_derive_darling::FromArgMatches>::update_from_arg_matches(
                         #update_ident,
                         #arg_matches_ident,
                         #prefix_ident
                     )?
-                }
-            } else {
+                },
+                (false, None) => from_arg_matches,
+                (true, Some(update_ident)) => quote! {
+                    if #arg_matches_ident
+                        .subcommand_name()
+                        .map_or_else(
+                            <#ty as clap_derive_darling::Subcommand>::has_default_subcommand,
+                            <#ty as clap_derive_darling::Subcommand>::has_subcommand,
+                        )
+                    {
+                        if let Some(clap_subcommand) = #update_ident.as_mut() {
+                            <#ty as clap_derive_darling::FromArgMatches>::update_from_arg_matches(
+                                clap_subcommand,
+                                #arg_matches_ident,
+                                #prefix_ident
+                            )?
+                        } else {
+                            *#update_ident = Some(#from_arg_matches);
+                        }
+                    }
+                },
+                (true, None) => quote! {
+                    if #arg_matches_ident
+                        .subcommand_name()
+                        .map_or_else(
+                            <#ty as clap_derive_darling::Subcommand>::has_default_subcommand,
+                            <#ty as clap_derive_darling::Subcommand>::has_subcommand,
+                        )
+                    {
+                        Some(#from_arg_matches)
+                    } else {
+                        None
+                    }
+                },
+            };
+
+            if boxed {
+                let parent_name = self.get_parent_or()?.get_name().unwrap_or_default();
+
                 quote! {
-                    <#ty as clap_derive_darling::FromArgMatches>::from_arg_matches(#arg_matches_ident, #prefix_ident.clone())?
+                    {
+                        let #arg_matches_ident = &clap_derive_darling::#boxed_subcommand_matches::<#ty>(
+                            #parent_name,
+                            #arg_matches_ident,
+                            #prefix_ident.clone(),
+                        )?;
+                        #parse
+                    }
                 }
+            } else {
+                parse
             }
         } else if let Some(skip) = self.skip.as_ref() {
             match skip {
@@ -720,8 +1330,8 @@ impl ClapField {
                         clap_derive_darling::FromArgMatches::update_from_arg_matches(
                             #update_ident,
                             #arg_matches_ident,
-                            #prefix_ident
-                        )
+                            #prefix_ident.clone()
+                        )?
                     }
                 }
             } else {
@@ -729,7 +1339,7 @@ impl ClapField {
                     {
                         #subprefix
 
-                        clap_derive_darling::FromArgMatches::from_arg_matches(#arg_matches_ident, #prefix_ident).unwrap()
+                        clap_derive_darling::FromArgMatches::from_arg_matches(#arg_matches_ident, #prefix_ident.clone())?
                     }
                 }
             }
@@ -737,18 +1347,55 @@ impl ClapField {
             let name = self.get_name_or()?;
 
             let parse = self.get_parse_defaulted()?;
-            let parse_expr = parse.parse()?;
+            let parse_expr = parse.parse_fallible()?;
 
             let name_ident = self.get_name_ident();
 
+            let flag_ident = self.get_flag_ident();
+
             let required_ident = self.to_tokens_required_ident(&name_ident, &name)?;
+            let flag = if matches!(arg_type, ClapArgType::Bool) {
+                None
+            } else {
+                Some(self.to_tokens_flag()?)
+            };
+            let occurrences = self.to_tokens_occurrences();
+
+            let canonical = if self.ignore_case {
+                self.get_possible_values()?.map(|possible_values| {
+                    quote! {
+                        let s = [#(#possible_values),*]
+                            .iter()
+                            .copied()
+                            .find(|v| v.eq_ignore_ascii_case(s))
+                            .unwrap_or(s);
+                    }
+                })
+            } else {
+                None
+            };
+
+            let arg_enum_type = self.get_arg_enum_type(&stripped_type_path)?;
+
+            let ignore_case = if self.ignore_case {
+                quote! { true }
+            } else {
+                quote! { <#arg_enum_type as clap_derive_darling::ArgEnum>::ignore_case() }
+            };
+
+            let from_mirror = self.with.as_ref().map(|with| {
+                quote! {
+                    .map(<#stripped_type_path as ::std::convert::From<#with>>::from)
+                }
+            });
 
             let mapper = if self.arg_enum {
                 quote! {
-                    <#stripped_type_path as clap_derive_darling::ArgEnum>::from_str(s, false)
+                    <#arg_enum_type as clap_derive_darling::ArgEnum>::from_str(s, #ignore_case)
+                        #from_mirror
                         .map_err(|err| clap::Error::raw(
                             clap::ErrorKind::ValueValidation,
-                            format!("Invalid value for {}: {}", &#name_ident, &err)
+                            format!("Invalid value for {}: {}", &#flag_ident, &err)
                         ))
                 }
             } else {
@@ -756,16 +1403,58 @@ impl ClapField {
                     #parse_expr(s).map_err(|err| {
                         clap::Error::raw(
                             clap::ErrorKind::ValueValidation,
-                            format!("Invalid value for {}: {}", &#name_ident, &err)
+                            format!("Invalid value for {}: {}", &#flag_ident, &err)
                         )
                     })
                 }
             };
 
+            let mapper = match canonical {
+                Some(canonical) => quote! {
+                    {
+                        #canonical
+                        #mapper
+                    }
+                },
+                None => mapper,
+            };
+
             let builder = if matches!(arg_type, ClapArgType::Bool) {
                 quote! {
                     #arg_matches_ident.is_present(#name_ident)
                 }
+            } else if self.pseudo_values {
+                quote! {
+                    #arg_matches_ident
+                        .values_of(&#name_ident)
+                        .map(|v| {
+                            let v = v.collect::<Vec<_>>();
+                            let count = v.len();
+
+                            v.into_iter().map(|s| -> Result<Vec<#stripped_type_path>, clap::Error> {
+                                if clap::PossibleValue::new("all").matches(s, #ignore_case) {
+                                    Ok(<#arg_enum_type as clap_derive_darling::ArgEnum>::value_variants()
+                                        .iter()
+                                        .cloned()
+                                        .map(::std::convert::From::from)
+                                        .collect())
+                                } else if clap::PossibleValue::new("none").matches(s, #ignore_case) {
+                                    if count > 1 {
+                                        return Err(clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: none can't be combined with other values", &#flag_ident)
+                                        ));
+                                    }
+
+                                    Ok(Vec::new())
+                                } else {
+                                    #mapper.map(|v| vec![v])
+                                }
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                        })
+                        .map(|v| v.map(|v| v.into_iter().flatten().collect()))
+                }
             } else if matches!(arg_type, ClapArgType::VecT | ClapArgType::OptionVecT) {
                 quote! {
                     #arg_matches_ident
@@ -774,7 +1463,7 @@ impl ClapField {
                             v.map(|s| #mapper)
                             // .collect()
                         })
-                        .map(|v| v.collect::<Result<Vec<_>, _>>())
+                        .map(|v| v.collect::<Result<_, _>>())
                 }
             } else {
                 quote! {
@@ -793,13 +1482,16 @@ impl ClapField {
                         None
                     }
                 }
-            } else if matches!(
-                arg_type,
-                ClapArgType::OptionOptionT | ClapArgType::OptionVecT
-            ) {
+            } else if matches!(arg_type, ClapArgType::OptionVecT) {
                 quote! {
-                    #builder
-                        .transpose()?
+                    if #arg_matches_ident.is_present(&#name_ident) {
+                        Some(
+                            #builder
+                                .unwrap_or_else(|| Ok(Default::default()))?
+                        )
+                    } else {
+                        None
+                    }
                 }
             } else if matches!(arg_type, ClapArgType::T) {
                 quote! {
@@ -807,14 +1499,14 @@ impl ClapField {
                         .ok_or_else(|| {
                             clap::Error::raw(
                                 clap::ErrorKind::ValueValidation,
-                                format!("Invalid value for {}", &#name_ident)
+                                format!("Invalid value for {}", &#flag_ident)
                             )
                         })?
                 }
             } else if matches!(arg_type, ClapArgType::VecT) {
                 quote! {
                     #builder
-                        .unwrap_or_else(|| Ok(Vec::new()))?
+                        .unwrap_or_else(|| Ok(Default::default()))?
                 }
             } else {
                 builder
@@ -823,6 +1515,8 @@ impl ClapField {
             quote! {
                 {
                     #required_ident
+                    #flag
+                    #occurrences
                     #builder
                 }
             }
@@ -144,7 +144,7 @@ fn test_inherit_and_defaults() {
     let fields = get_fields(&struct_parser);
     let by_name = get_by_name(&fields);
 
-    assert!(matches!(by_name["test0"], None));
+    assert!(by_name["test0"].is_none());
     assert!(matches!(
         by_name["test1"],
         Some(ClapFieldParse::FromStr(Override::Inherit))
@@ -205,7 +205,7 @@ fn test_explicit() {
     let fields = get_fields(&struct_parser);
     let by_name = get_by_name(&fields);
 
-    assert!(matches!(by_name["test0"], None));
+    assert!(by_name["test0"].is_none());
     assert!(matches!(
         by_name["test1"],
         Some(ClapFieldParse::FromStr(Override::Explicit(..)))
@@ -295,8 +295,59 @@ struct Test {
 
     let output = quote! { fn rar() { #(#augment_fields)* } };
 
-    println!("{}", output.to_string());
+    println!("{}", output);
 
     file.write_all(rustfmt_ext(output).unwrap().as_bytes())
         .unwrap();
 }
+
+#[test]
+fn test_to_tokens_augment_validations() {
+    let mut mint = Mint::new("tests/goldenfiles/field");
+    let mut file = mint
+        .new_goldenfile("test_to_tokens_augment_validations.rs")
+        .unwrap();
+
+    let input = r#"
+struct Test {
+    #[clap(long, range = "1..=65535")]
+    port: u16,
+
+    #[clap(long, min_len = 1, max_len = 8, non_empty)]
+    code: String,
+
+    #[clap(long, regex = "^[a-z]+$")]
+    name: Option<String>,
+
+    #[clap(long, min_occurrences = 1, max_occurrences = 3)]
+    tag: Vec<String>,
+}
+"#;
+
+    let parsed = syn::parse_str(input).unwrap();
+    let conf_struct = StructParser::from_derive_input(&parsed).unwrap();
+
+    let augment_fields = conf_struct.to_tokens_augment_args_fields().unwrap();
+
+    let output = quote! { fn rar() { #(#augment_fields)* } };
+
+    file.write_all(rustfmt_ext(output).unwrap().as_bytes())
+        .unwrap();
+}
+
+#[test]
+fn test_invalid_regex_error() {
+    let input = r#"
+struct Test {
+    #[clap(long, regex = "^[a-z+$")]
+    name: String,
+}
+"#;
+
+    let parsed = syn::parse_str(input).unwrap();
+    let conf_struct = StructParser::from_derive_input(&parsed).unwrap();
+
+    let err = conf_struct.to_tokens_augment_args_fields().unwrap_err();
+
+    assert!(err.to_string().contains("unclosed character class"));
+}
@@ -28,7 +28,7 @@ use subcommand::ClapSubcommand;
 
 use common::ClapTokensResultAuto;
 
-#[proc_macro_derive(Parser, attributes(clap, doc))]
+#[proc_macro_derive(Parser, attributes(clap))]
 pub fn derive_parser(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input);
     let conf_struct = ClapParser::from_derive_input(&input).expect("Wrong options");
@@ -36,7 +36,7 @@ pub fn derive_parser(input: TokenStream) -> TokenStream {
     conf_struct.to_tokens().into()
 }
 
-#[proc_macro_derive(Args, attributes(clap, doc))]
+#[proc_macro_derive(Args, attributes(clap))]
 pub fn derive_args(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input);
     let conf_struct = ClapArgs::from_derive_input(&input).expect("Wrong options");
@@ -60,18 +60,6 @@ pub fn derive_arg_enum(input: TokenStream) -> TokenStream {
     conf_struct.to_tokens().into()
 }
 
-#[derive(Debug, Clone, Copy, FromMeta)]
-enum ClapType {
-    Parser,
-    Args,
-}
-
-impl Default for ClapType {
-    fn default() -> Self {
-        ClapType::Parser
-    }
-}
-
 #[derive(Debug, Clone, Copy, FromMeta)]
 pub(crate) enum RenameAll {
     #[darling(rename = "camelCase")]
@@ -1,25 +1,41 @@
 use std::vec;
 
-use darling::{ast, util::Override, FromDeriveInput, FromVariant, Result};
+use darling::{ast, util::Override, Error, FromDeriveInput, FromMeta, FromVariant, Result};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::Ident;
 
 use crate::{
     common::{
         ClapCommonIdents, ClapDocAboutMarker, ClapDocCommon, ClapDocCommonAuto, ClapFieldParent,
         ClapFieldStructs, ClapFields, ClapIdentName, ClapIdentNameContainer, ClapParserArgsCommon,
-        ClapTokensResult, ClapTraitImpls,
+        ClapTokensResult, ClapTraitImpls, VecStringAttr,
     },
     field::ClapField,
     RenameAll, RenameAllCasing,
 };
 
+/// Enum level `#[clap(run(...))]` options
+#[derive(Debug, Clone, Default, FromMeta)]
+pub(crate) struct ClapSubcommandRun {
+    #[darling(default)]
+    context: Option<syn::Type>,
+    #[darling(default)]
+    output: Option<syn::Type>,
+}
+
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(clap), supports(enum_any))]
 pub struct ClapSubcommand {
     ident: Ident,
     data: ast::Data<ClapSubcommandVariant, ()>,
+
+    #[darling(default)]
+    run: Option<Override<ClapSubcommandRun>>,
+    #[darling(default)]
+    args: Option<syn::Type>,
+    #[darling(default)]
+    env_scope: bool,
 }
 
 impl ClapIdentName for ClapSubcommand {
@@ -35,10 +51,14 @@ impl ClapTokensResult for ClapSubcommand {
     fn to_tokens_result(&self) -> Result<TokenStream> {
         let impl_from_arg_matches = self.to_tokens_impl_from_arg_matches()?;
         let impl_subcommand = self.to_tokens_impl_subcommand()?;
+        let impl_subcommand_args = self.to_tokens_impl_subcommand_args()?;
+        let impl_run = self.to_tokens_impl_run()?;
 
         Ok(quote! {
             #impl_from_arg_matches
             #impl_subcommand
+            #impl_subcommand_args
+            #impl_run
         })
     }
 }
@@ -67,28 +87,141 @@ impl ClapSubcommand {
             .map(|mut v| {
                 let container = ClapIdentNameContainer::from(self);
                 v.parent = Some(Box::new(container));
+                v.shared_args = self.args.clone();
+                v.env_scope = self.env_scope;
                 v
             })
             .collect()
     }
 
+    fn has_external_subcommand(&self) -> bool {
+        self.get_variants()
+            .iter()
+            .any(|v| v.external_subcommand && !v.skip)
+    }
+
+    fn get_variants_external_last(&self) -> Result<Vec<ClapSubcommandVariant>> {
+        let (external, mut variants): (Vec<_>, Vec<_>) = self
+            .get_variants()
+            .into_iter()
+            .partition(|v| v.external_subcommand && !v.skip);
+
+        if let Some(extra) = external.get(1) {
+            return Err(
+                Error::custom("Only one external_subcommand variant is allowed")
+                    .with_span(&extra.ident),
+            );
+        }
+
+        variants.extend(external);
+
+        Ok(variants)
+    }
+
+    fn get_default_subcommand(&self) -> Result<Option<ClapSubcommandVariant>> {
+        let mut defaults = self
+            .get_variants()
+            .into_iter()
+            .filter(|v| v.default_subcommand && !v.skip);
+
+        let default = defaults.next();
+
+        if let Some(extra) = defaults.next() {
+            return Err(
+                Error::custom("Only one default_subcommand variant is allowed")
+                    .with_span(&extra.ident),
+            );
+        }
+
+        if let Some(default) = default.as_ref() {
+            if default.external_subcommand || default.flatten {
+                return Err(Error::custom(
+                    "default_subcommand cannot be combined with external_subcommand or flatten",
+                )
+                .with_span(&default.ident));
+            }
+        }
+
+        Ok(default)
+    }
+
     fn to_tokens_impl_from_arg_matches(&self) -> Result<TokenStream> {
         let ident = &self.ident;
         let arg_matches_ident = self.get_arg_matches_ident();
         let prefix_ident = self.get_prefix_ident();
 
-        let from_arg_matches_variants = self
-            .get_variants()
+        // Local variants are tried first, then flattened enums, in declaration order, and the
+        // external subcommand last as it takes any name
+        let (external_variants, variants): (Vec<_>, Vec<_>) = self
+            .get_variants_external_last()?
+            .into_iter()
+            .partition(|v| v.external_subcommand && !v.skip);
+        let (flatten_variants, variants): (Vec<_>, Vec<_>) =
+            variants.into_iter().partition(|v| v.flatten && !v.skip);
+
+        let from_arg_matches_variants = variants
             .iter()
             .map(|v| v.to_tokens_from_arg_matches_variant())
             .collect::<Result<Vec<_>>>()?;
 
+        let from_arg_matches_flatten_variants = flatten_variants
+            .iter()
+            .map(|v| v.to_tokens_from_arg_matches_flatten_variant())
+            .collect::<Result<Vec<_>>>()?;
+
+        let from_arg_matches_external_variants = external_variants
+            .iter()
+            .map(|v| v.to_tokens_from_arg_matches_variant())
+            .collect::<Result<Vec<_>>>()?;
         let update_from_arg_matches_variants = self
             .get_variants()
             .iter()
             .map(|v| v.to_tokens_update_from_arg_matches_variant())
             .collect::<Result<Vec<_>>>()?;
 
+        let unrecognized_subcommand = if self.has_external_subcommand() {
+            None
+        } else {
+            Some(quote! {
+                Err(clap::Error::raw(clap::ErrorKind::UnrecognizedSubcommand, format!("The subcommand '{}' watn't recognized", clap_name)))
+            })
+        };
+
+        let missing_subcommand_error = quote! {
+            Err(clap::Error::raw(clap::ErrorKind::MissingSubcommand, "A subcommand is required but one was not provided"))
+        };
+
+        // The default subcommand is parsed on its own from an empty command line, so its args
+        // take their default values and env vars
+        let default_subcommand = self.get_default_subcommand()?;
+
+        let missing_subcommand = if let Some(default) = &default_subcommand {
+            let name = default.get_name_or()?;
+            let app_ident = default.get_app_ident();
+            let augment_default = default.to_tokents_augment_subcommands_variant(false)?;
+            let from_arg_matches_default = default.to_tokens_from_arg_matches_variant()?;
+
+            quote! {
+                let #app_ident = clap::App::new(#name);
+                #augment_default
+                let #app_ident = #app_ident
+                    .find_subcommand(#name)
+                    .expect("The default subcommand is always added")
+                    .clone();
+
+                let clap_name = #name;
+                let sub_arg_matches = #app_ident
+                    .no_binary_name(true)
+                    .try_get_matches_from(::std::iter::empty::<::std::ffi::OsString>())?;
+                let #arg_matches_ident = &sub_arg_matches;
+
+                #from_arg_matches_default
+                #missing_subcommand_error
+            }
+        } else {
+            missing_subcommand_error
+        };
+
         Ok(quote! {
             impl clap_derive_darling::FromArgMatches for #ident {
                 fn from_arg_matches(#arg_matches_ident: &clap::ArgMatches, #prefix_ident: Vec<&'static str>) -> Result<Self, clap::Error> {
@@ -98,9 +231,17 @@ impl ClapSubcommand {
 
                             #(#from_arg_matches_variants)*
                         }
-                        Err(clap::Error::raw(clap::ErrorKind::UnrecognizedSubcommand, format!("The subcommand '{}' watn't recognized", clap_name)))
+
+                        #(#from_arg_matches_flatten_variants)*
+
+                        {
+                            let #arg_matches_ident = sub_arg_matches;
+
+                            #(#from_arg_matches_external_variants)*
+                        }
+                        #unrecognized_subcommand
                     } else {
-                        Err(clap::Error::raw(clap::ErrorKind::MissingSubcommand, "A subcommand is required but one was not provided"))
+                        #missing_subcommand
                     }
                 }
                 fn update_from_arg_matches(&mut self, #arg_matches_ident: &clap::ArgMatches, #prefix_ident: Vec<&'static str>) -> Result<(), clap::Error> {
@@ -119,6 +260,107 @@ impl ClapSubcommand {
         })
     }
 
+    fn to_tokens_impl_subcommand_args(&self) -> Result<TokenStream> {
+        let ident = &self.ident;
+
+        let args = match self.args.as_ref() {
+            Some(args) => args,
+            None => return Ok(quote! {}),
+        };
+
+        let variants = self
+            .get_variants()
+            .into_iter()
+            .filter(|v| !v.skip && !v.external_subcommand)
+            .collect::<Vec<_>>();
+
+        if let Some(variant) = variants.iter().find(|v| v.flatten) {
+            return Err(Error::custom(
+                "flatten variants cannot be combined with shared #[clap(args = ...)]",
+            )
+            .with_span(&variant.ident));
+        }
+
+        let name_matches = variants
+            .iter()
+            .map(|v| v.to_tokens_name_matches())
+            .collect::<Result<Vec<_>>>()?;
+        let subprefixes = variants
+            .iter()
+            .map(|v| v.to_tokens_subprefix())
+            .collect::<Vec<_>>();
+
+        let prefix_ident = self.get_prefix_ident();
+
+        Ok(quote! {
+            impl clap_derive_darling::SubcommandArgs for #ident {
+                type Args = #args;
+
+                fn has_shared_args(clap_name: &str) -> bool {
+                    #(
+                        if #name_matches {
+                            return true;
+                        }
+                    )*
+
+                    false
+                }
+
+                fn shared_args_prefix(clap_name: &str, #prefix_ident: Vec<&'static str>) -> Vec<&'static str> {
+                    #(
+                        if #name_matches {
+                            return #subprefixes;
+                        }
+                    )*
+
+                    #prefix_ident
+                }
+            }
+        })
+    }
+
+    fn to_tokens_impl_run(&self) -> Result<TokenStream> {
+        let ident = &self.ident;
+
+        let run = match self.run.as_ref() {
+            Some(run) => run.clone().unwrap_or_default(),
+            None => {
+                if let Some(variant) = self.get_variants().iter().find(|v| v.run.is_some()) {
+                    return Err(Error::custom(
+                        "#[clap(run)] on a variant requires #[clap(run)] on the enum",
+                    )
+                    .with_span(&variant.ident));
+                }
+                return Ok(quote! {});
+            }
+        };
+
+        let context = run
+            .context
+            .map_or_else(|| quote! { () }, |ty| quote! { #ty });
+        let output = run
+            .output
+            .map_or_else(|| quote! { () }, |ty| quote! { #ty });
+
+        let run_variants = self
+            .get_variants()
+            .iter()
+            .map(|v| v.to_tokens_run_variant())
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(quote! {
+            impl clap_derive_darling::Run<#context> for #ident {
+                type Output = #output;
+
+                fn run(self, clap_context: #context) -> Self::Output {
+                    match self {
+                        #(#run_variants)*
+                    }
+                }
+            }
+        })
+    }
+
     fn to_tokens_impl_subcommand(&self) -> Result<TokenStream> {
         let ident = &self.ident;
         let app_ident = self.get_app_ident();
@@ -127,7 +369,7 @@ impl ClapSubcommand {
         let augment_subcommands_variants = self
             .get_variants()
             .iter()
-            .map(|v| v.to_tokents_augment_subcommands_variant())
+            .map(|v| v.to_tokents_augment_subcommands_variant(false))
             .collect::<Result<Vec<_>>>()?;
 
         let augment_subcommands_for_update_variants = self
@@ -137,13 +379,45 @@ impl ClapSubcommand {
             .collect::<Result<Vec<_>>>()?;
 
         let has_subcommands = self
-            .get_variants()
+            .get_variants_external_last()?
             .iter()
             .map(|v| v.to_tokens_has_subcommand())
             .collect::<Result<Vec<_>>>()?;
 
+        let subcommand_paths = self
+            .get_variants()
+            .iter()
+            .map(|v| v.to_tokens_subcommand_path())
+            .collect::<Result<Vec<_>>>()?;
+
+        let not_found = if self.has_external_subcommand() {
+            None
+        } else {
+            Some(quote! { false })
+        };
+
+        let has_default_subcommand = if self.get_default_subcommand()?.is_some() {
+            Some(quote! {
+                fn has_default_subcommand() -> bool {
+                    true
+                }
+            })
+        } else {
+            None
+        };
+
+        let has_external_subcommand = if self.has_external_subcommand() {
+            Some(quote! {
+                const HAS_EXTERNAL_SUBCOMMAND: bool = true;
+            })
+        } else {
+            None
+        };
+
         Ok(quote! {
             impl clap_derive_darling::Subcommand for #ident {
+                #has_external_subcommand
+
                 fn augment_subcommands<'b>(#app_ident: clap::App<'b>, #prefix_ident: Vec<&'static str>) -> clap::App<'b> {
                     #(#augment_subcommands_variants)*
 
@@ -157,13 +431,30 @@ impl ClapSubcommand {
                 fn has_subcommand(clap_name: &str) -> bool {
                     #(#has_subcommands)*
 
-                    false
+                    #not_found
+                }
+                #has_default_subcommand
+                fn subcommand_path(&self) -> Vec<&str> {
+                    match self {
+                        #(#subcommand_paths)*
+                    }
                 }
             }
         })
     }
 }
 
+/// How a subcommand's args see the prefix of the enclosing context
+#[derive(Debug, Clone, Copy, Default, PartialEq, FromMeta)]
+#[darling(rename_all = "snake_case")]
+pub(crate) enum ClapSubcommandPrefix {
+    /// Start again from an empty prefix
+    Reset,
+    /// Keep the prefix the enum itself was given, as struct variants always did
+    #[default]
+    Inherit,
+}
+
 #[derive(Debug, Clone, FromVariant)]
 #[darling(attributes(clap), forward_attrs(doc))]
 pub(crate) struct ClapSubcommandVariant {
@@ -173,6 +464,10 @@ pub(crate) struct ClapSubcommandVariant {
 
     #[darling(skip)]
     parent: Option<Box<dyn ClapFieldParent>>,
+    #[darling(skip)]
+    shared_args: Option<syn::Type>,
+    #[darling(skip)]
+    env_scope: bool,
 
     #[darling(default)]
     name: Option<String>,
@@ -194,6 +489,30 @@ pub(crate) struct ClapSubcommandVariant {
     skip: bool,
     #[darling(default)]
     external_subcommand: bool,
+    #[darling(default)]
+    flatten: bool,
+    #[darling(default)]
+    default_subcommand: bool,
+    #[darling(default)]
+    prefix: ClapSubcommandPrefix,
+    #[darling(default)]
+    run: Option<Override<syn::Path>>,
+    #[darling(default, multiple)]
+    alias: Vec<String>,
+    #[darling(default)]
+    aliases: Option<VecStringAttr>,
+    #[darling(default, multiple)]
+    visible_alias: Vec<String>,
+    #[darling(default)]
+    visible_aliases: Option<VecStringAttr>,
+    #[darling(default)]
+    short_flag: Option<String>,
+    #[darling(default)]
+    long_flag: Option<String>,
+    #[darling(default)]
+    hide: bool,
+    #[darling(default)]
+    display_order: Option<usize>,
 
     #[darling(skip, default = "crate::default_rename_all")]
     rename_all: RenameAll,
@@ -221,11 +540,178 @@ impl ClapIdentName for ClapSubcommandVariant {
 impl ClapCommonIdents for ClapSubcommandVariant {}
 
 impl ClapSubcommandVariant {
+    fn get_tuple_fieldstructs(&self) -> Result<Vec<ClapField>> {
+        let mut fields = self.get_fieldstructs();
+
+        let type_names = fields
+            .iter()
+            .map(|f| {
+                if f.name.is_some() {
+                    return Ok(None);
+                }
+                f.get_type_name()?.map(Some).ok_or_else(|| {
+                    Error::custom(
+                        "Cannot derive a name for this tuple field, add #[clap(name = \"...\")]",
+                    )
+                    .with_span(&f.ty)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (index, field) in fields.iter_mut().enumerate() {
+            field.ident = Some(format_ident!("clap_arg_{}", index));
+
+            if let Some(type_name) = &type_names[index] {
+                let duplicates = type_names
+                    .iter()
+                    .filter(|n| n.as_ref() == Some(type_name))
+                    .count();
+
+                field.name = Some(if duplicates > 1 {
+                    let position = type_names[..=index]
+                        .iter()
+                        .filter(|n| n.as_ref() == Some(type_name))
+                        .count();
+                    format!("{}_{}", type_name, position)
+                } else {
+                    type_name.clone()
+                });
+            }
+        }
+
+        Ok(fields)
+    }
+
+    fn is_external_subcommand_os(&self) -> Result<bool> {
+        let fields = self.get_fieldstructs();
+
+        let error = || {
+            Error::custom("external_subcommand must be a newtype of Vec<String> or Vec<OsString>")
+                .with_span(&self.ident)
+        };
+
+        if !self.fields.is_newtype() {
+            return Err(error());
+        }
+
+        match fields[0].get_type_name()?.as_deref() {
+            Some("String") => Ok(false),
+            Some("OsString") => Ok(true),
+            _ => Err(error()),
+        }
+    }
+
+    fn get_aliases(&self) -> (Vec<String>, Vec<String>) {
+        let hidden = self
+            .alias
+            .iter()
+            .cloned()
+            .chain(self.aliases.iter().flat_map(|v| v.to_strings()))
+            .collect();
+        let visible = self
+            .visible_alias
+            .iter()
+            .cloned()
+            .chain(self.visible_aliases.iter().flat_map(|v| v.to_strings()))
+            .collect();
+
+        (hidden, visible)
+    }
+
+    fn to_tokens_name_matches(&self) -> Result<TokenStream> {
+        let name = self.get_name_or()?;
+        let (hidden, visible) = self.get_aliases();
+
+        Ok(if hidden.is_empty() && visible.is_empty() {
+            quote! { #name == clap_name }
+        } else {
+            quote! { [#name, #(#hidden,)* #(#visible,)*].contains(&clap_name) }
+        })
+    }
+
+    fn to_tokens_subcommand_settings(&self) -> Result<TokenStream> {
+        let (hidden, visible) = self.get_aliases();
+
+        let short_flag = self
+            .short_flag
+            .as_ref()
+            .map(|short| {
+                short
+                    .chars()
+                    .next()
+                    .ok_or_else(|| Error::unknown_value(short).with_span(&self.ident))
+            })
+            .transpose()?
+            .map(|short| quote! { .short_flag(#short) });
+        let long_flag = self
+            .long_flag
+            .as_ref()
+            .map(|long| quote! { .long_flag(#long) });
+        let hide = if self.hide {
+            Some(quote! { .hide(true) })
+        } else {
+            None
+        };
+        let display_order = self
+            .display_order
+            .map(|order| quote! { .display_order(#order) });
+
+        Ok(quote! {
+            #(.alias(#hidden))*
+            #(.visible_alias(#visible))*
+            #short_flag
+            #long_flag
+            #hide
+            #display_order
+        })
+    }
+
+    fn to_tokens_subprefix(&self) -> TokenStream {
+        let prefix_ident = self.get_prefix_ident();
+
+        match self.prefix {
+            ClapSubcommandPrefix::Reset => quote! { Vec::new() },
+            ClapSubcommandPrefix::Inherit => quote! { #prefix_ident.clone() },
+        }
+    }
+
+    fn get_flatten_type(&self) -> Result<syn::Type> {
+        if !self.fields.is_newtype() {
+            return Err(
+                Error::custom("flatten must be used on a newtype variant of a Subcommand")
+                    .with_span(&self.ident),
+            );
+        }
+
+        Ok(self.get_fieldstructs()[0].ty.clone())
+    }
+
+    fn to_tokens_from_arg_matches_flatten_variant(&self) -> Result<TokenStream> {
+        let ident = &self.ident;
+        let arg_matches_ident = self.get_arg_matches_ident();
+        let prefix_ident = self.get_prefix_ident();
+        let parent_ident = self.get_parent_or()?.get_ident_or()?;
+
+        let ty = self.get_flatten_type()?;
+
+        Ok(quote! {
+            if <#ty as clap_derive_darling::Subcommand>::has_subcommand(clap_name) {
+                return Ok(#parent_ident::#ident(
+                    <#ty as clap_derive_darling::FromArgMatches>::from_arg_matches(
+                        #arg_matches_ident,
+                        #prefix_ident.clone(),
+                    )?,
+                ));
+            }
+        })
+    }
+
     fn to_tokens_from_arg_matches_variant(&self) -> Result<TokenStream> {
         let ident = &self.ident;
         let arg_matches_ident = self.get_arg_matches_ident();
         let prefix_ident = self.get_prefix_ident();
-        let name = self.get_name_or()?;
+        let subprefix = self.to_tokens_subprefix();
+        let name_matches = self.to_tokens_name_matches()?;
         let parent_ident = self.get_parent_or()?.get_ident_or()?;
 
         let fields = self.get_fieldstructs();
@@ -233,29 +719,33 @@ impl ClapSubcommandVariant {
         Ok(if self.skip {
             quote! {}
         } else if self.external_subcommand {
+            let (string_ty, values_of) = if self.is_external_subcommand_os()? {
+                (quote!(::std::ffi::OsString), quote!(values_of_os))
+            } else {
+                (quote!(::std::string::String), quote!(values_of))
+            };
+
             quote! {
-                if #name == clap_name {
-                    return Ok(#parent_ident::#ident(
-                        ::std::iter::once(::std::string::String::from(clap_name))
-                            .chain(
-                                #arg_matches_ident
-                                    .values_of("")
-                                    .into_iter()
-                                    .flatten()
-                                    .map(::std::string::String::from)
-                            )
-                            .collect::<Vec<_>>()
-                    ))
-                }
+                return Ok(#parent_ident::#ident(
+                    ::std::iter::once(#string_ty::from(clap_name))
+                        .chain(
+                            #arg_matches_ident
+                                .#values_of("")
+                                .into_iter()
+                                .flatten()
+                                .map(#string_ty::from)
+                        )
+                        .collect::<Vec<_>>()
+                ));
             }
         } else if self.fields.is_newtype() {
             let first_field_ty = &fields[0].ty;
             quote! {
-                if #name == clap_name {
+                if #name_matches {
                     return Ok(#parent_ident::#ident(
                         <#first_field_ty as clap_derive_darling::FromArgMatches>::from_arg_matches(
                             #arg_matches_ident,
-                            #prefix_ident,
+                            #subprefix,
                         )?,
                     ));
                 }
@@ -264,16 +754,36 @@ impl ClapSubcommandVariant {
             let from_arg_matches_fields = self.to_tokens_from_arg_matches_fields()?;
 
             quote! {
-                if #name == clap_name {
+                if #name_matches {
+                    #[allow(unused_variables)]
+                    let #prefix_ident: Vec<&'static str> = #subprefix;
                     return Ok(#parent_ident::#ident {
                         #(#from_arg_matches_fields)*
                     })
                 }
             }
         } else if self.fields.is_tuple() {
-            unimplemented!("Variant type tuple not implemented");
+            let from_arg_matches_fields = self
+                .get_tuple_fieldstructs()?
+                .iter()
+                .map(|f| f.to_tokens_parse_value())
+                .collect::<Result<Vec<_>>>()?;
+
+            quote! {
+                if #name_matches {
+                    #[allow(unused_variables)]
+                    let #prefix_ident: Vec<&'static str> = #subprefix;
+                    return Ok(#parent_ident::#ident(
+                        #(#from_arg_matches_fields),*
+                    ));
+                }
+            }
         } else if self.fields.is_unit() {
-            unimplemented!("Variant type unit not implemented");
+            quote! {
+                if #name_matches {
+                    return Ok(#parent_ident::#ident);
+                }
+            }
         } else {
             unimplemented!("Unknown variant type")
         })
@@ -283,8 +793,9 @@ impl ClapSubcommandVariant {
         let ident = &self.ident;
         let arg_matches_ident = self.get_arg_matches_ident();
         let prefix_ident = self.get_prefix_ident();
+        let subprefix = self.to_tokens_subprefix();
 
-        let name = self.get_name_or()?;
+        let name_matches = self.to_tokens_name_matches()?;
         let parent_ident = self.get_parent_or()?.get_ident_or()?;
 
         let fields_ref_mut = self
@@ -300,28 +811,30 @@ impl ClapSubcommandVariant {
 
         Ok(if self.skip {
             quote! {}
-        } else if self.external_subcommand {
+        } else if self.flatten {
+            let ty = self.get_flatten_type()?;
+
             quote! {
-                #parent_ident::#ident(ref mut clap_arg) if #name == clap_name => {
-                    *clap_arg = ::std::iter::once(::std::string::String::from(clap_name))
-                        .chain(
-                            #arg_matches_ident
-                                .values_of("")
-                                .into_iter()
-                                .flatten()
-                                .map(::std::string::String::from)
-                        )
-                        .collect::<Vec<_>>();
+                #parent_ident::#ident(ref mut clap_arg) if <#ty as clap_derive_darling::Subcommand>::has_subcommand(clap_name) => {
+                    clap_derive_darling::FromArgMatches::update_from_arg_matches(
+                        clap_arg,
+                        #arg_matches_ident,
+                        #prefix_ident.clone(),
+                    )?
                 }
             }
+        } else if self.external_subcommand {
+            // External subcommands are rebuilt by the fallback arm, which also
+            // handles switching to and from known subcommands
+            quote! {}
         } else if self.fields.is_newtype() {
             quote! {
-                #parent_ident::#ident(ref mut clap_arg) if #name == clap_name => {
+                #parent_ident::#ident(ref mut clap_arg) if #name_matches => {
                     let #arg_matches_ident = sub_arg_matches;
                     clap_derive_darling::FromArgMatches::update_from_arg_matches(
                         clap_arg,
                         sub_arg_matches,
-                        #prefix_ident,
+                        #subprefix,
                     )?
                 }
             }
@@ -333,102 +846,413 @@ impl ClapSubcommandVariant {
                 .collect::<Result<Vec<_>>>()?;
 
             quote! {
-                #parent_ident::#ident { #(#fields_ref_mut)* } if #name == clap_name => {
+                #parent_ident::#ident { #(#fields_ref_mut)* } if #name_matches => {
                     let #arg_matches_ident = sub_arg_matches;
+                    #[allow(unused_variables)]
+                    let #prefix_ident: Vec<&'static str> = #subprefix;
                     {
                         #(#update_from_arg_matches_raw)*
                     }
                 }
             }
         } else if self.fields.is_tuple() {
-            unimplemented!("Variant type tuple not implemented");
+            let fields = self.get_tuple_fieldstructs()?;
+
+            let fields_ref_mut = fields.iter().map(|f| {
+                let ident = &f.ident;
+                quote! { ref mut #ident }
+            });
+
+            let update_from_arg_matches_raw = fields
+                .iter()
+                .map(|f| f.to_tokens_update_from_arg_matches_raw())
+                .collect::<Result<Vec<_>>>()?;
+
+            quote! {
+                #parent_ident::#ident(#(#fields_ref_mut),*) if #name_matches => {
+                    let #arg_matches_ident = sub_arg_matches;
+                    #[allow(unused_variables)]
+                    let #prefix_ident: Vec<&'static str> = #subprefix;
+                    {
+                        #(#update_from_arg_matches_raw)*
+                    }
+                }
+            }
         } else if self.fields.is_unit() {
-            unimplemented!("Variant type unit not implemented");
+            quote! {
+                #parent_ident::#ident if #name_matches => {}
+            }
         } else {
             unimplemented!("Unknown variant type")
         })
     }
 
-    fn to_tokents_augment_subcommands_variant(&self) -> Result<TokenStream> {
+    fn to_tokents_augment_subcommands_variant(&self, for_update: bool) -> Result<TokenStream> {
         let app_ident = self.get_app_ident();
 
         let name = self.get_name_or()?;
 
         let author_and_version = self.to_tokens_author_and_version();
+        let subcommand_settings = self.to_tokens_subcommand_settings()?;
         let app_call_help_about = self.to_tokens_app_call_help_about();
+        let prefix_ident = self.get_prefix_ident();
+        let subprefix = self.to_tokens_subprefix();
+
+        let augment_args = if for_update {
+            quote!(augment_args_for_update)
+        } else {
+            quote!(augment_args)
+        };
+
+        let new_subcommand = if let Some(shared_args) = &self.shared_args {
+            quote! {
+                <#shared_args as clap_derive_darling::Args>::#augment_args(clap::App::new(#name), #subprefix)
+            }
+        } else {
+            quote! { clap::App::new(#name) }
+        };
+
+        let add_subcommand = |subcommand: TokenStream| {
+            quote! {
+                let #app_ident = #app_ident.subcommand(#subcommand);
+            }
+        };
 
         let fields = self.get_fieldstructs();
 
+        if self.env_scope && !self.skip {
+            if let Some(field) = fields.iter().find(|f| f.flatten.is_some()) {
+                return Err(Error::custom(
+                    "env_scope can't reach flattened args, declare the args as fields of the variant",
+                )
+                .with_span(&field.ty));
+            }
+        }
+
         Ok(if self.skip {
             quote! {}
-        } else if self.external_subcommand {
+        } else if self.flatten {
+            let ty = self.get_flatten_type()?;
+
             quote! {
-                let #app_ident = #app_ident.subcommand({
-                    let clap_subcommand = clap::App::new(#name);
+                const _: () = assert!(
+                    !<#ty as clap_derive_darling::Subcommand>::HAS_EXTERNAL_SUBCOMMAND,
+                    "flatten can't be used on a subcommand enum with an external_subcommand",
+                );
+                let #app_ident = <#ty as clap_derive_darling::Subcommand>::augment_subcommands(#app_ident, #prefix_ident.clone());
+            }
+        } else if self.external_subcommand {
+            let allow_invalid_utf8 = if self.is_external_subcommand_os()? {
+                Some(quote! {
+                    let #app_ident = #app_ident.allow_invalid_utf8_for_external_subcommands(true);
+                })
+            } else {
+                None
+            };
 
-                    clap_subcommand
-                });
+            quote! {
                 let #app_ident = #app_ident.setting(clap::AppSettings::AllowExternalSubcommands);
+                #allow_invalid_utf8
             }
         } else if self.fields.is_newtype() {
+            if self.env_scope {
+                return Err(Error::custom(
+                    "env_scope can't reach the args of a newtype variant, declare the args as fields of the variant",
+                )
+                .with_span(&self.ident));
+            }
+
             let first_field_ty = &fields[0].ty;
-            quote! {
-                let #app_ident = #app_ident.subcommand({
-                    let clap_subcommand = clap::App::new(#name);
+            add_subcommand(quote! {
+                {
+                    let clap_subcommand = #new_subcommand;
 
                     let clap_subcommand = {
-                        <#first_field_ty as clap_derive_darling::Args>::augment_args(clap_subcommand, Vec::new())
+                        <#first_field_ty as clap_derive_darling::Args>::#augment_args(clap_subcommand, #subprefix)
                     };
 
                     clap_subcommand
                         #author_and_version
+                        #subcommand_settings
                         #app_call_help_about
-                });
-            }
+                }
+            })
         } else if self.fields.is_struct() {
             let augment = self
                 .get_fieldstructs()
                 .iter()
-                .map(|f| f.to_tokens_augment())
+                .map(|f| {
+                    if for_update {
+                        f.to_tokens_augment_for_update()
+                    } else {
+                        f.to_tokens_augment()
+                    }
+                })
                 .collect::<Result<Vec<_>>>()?;
 
-            quote! {
-                let #app_ident = #app_ident.subcommand({
-                    let clap_subcommand = clap::App::new(#name);
+            add_subcommand(quote! {
+                {
+                    let clap_subcommand = #new_subcommand;
                     {
                         let #app_ident = clap_subcommand;
+                        #[allow(unused_variables)]
+                        let #prefix_ident: Vec<&'static str> = #subprefix;
 
                         #(#augment)*
 
                         #app_ident
                             #author_and_version
+                            #subcommand_settings
                             #app_call_help_about
                     }
-                });
-            }
+                }
+            })
         } else if self.fields.is_tuple() {
-            unimplemented!("Variant type tuple not implemented");
+            let augment = self
+                .get_tuple_fieldstructs()?
+                .iter()
+                .map(|f| {
+                    if for_update {
+                        f.to_tokens_augment_for_update()
+                    } else {
+                        f.to_tokens_augment()
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            add_subcommand(quote! {
+                {
+                    let clap_subcommand = #new_subcommand;
+                    {
+                        let #app_ident = clap_subcommand;
+                        #[allow(unused_variables)]
+                        let #prefix_ident: Vec<&'static str> = #subprefix;
+
+                        #(#augment)*
+
+                        #app_ident
+                            #author_and_version
+                            #subcommand_settings
+                            #app_call_help_about
+                    }
+                }
+            })
         } else if self.fields.is_unit() {
-            unimplemented!("Variant type unit not implemented");
+            add_subcommand(quote! {
+                {
+                    let clap_subcommand = #new_subcommand;
+
+                    clap_subcommand
+                        #author_and_version
+                        #subcommand_settings
+                        #app_call_help_about
+                }
+            })
         } else {
             unimplemented!("Unknown variant type")
         })
     }
     fn to_tokents_augment_subcommands_for_update_variant(&self) -> Result<TokenStream> {
-        self.to_tokents_augment_subcommands_variant()
+        if self.flatten && !self.skip {
+            let app_ident = self.get_app_ident();
+            let prefix_ident = self.get_prefix_ident();
+            let ty = self.get_flatten_type()?;
+
+            Ok(quote! {
+                let #app_ident = <#ty as clap_derive_darling::Subcommand>::augment_subcommands_for_update(#app_ident, #prefix_ident.clone());
+            })
+        } else {
+            self.to_tokents_augment_subcommands_variant(true)
+        }
+    }
+    fn to_tokens_run_variant(&self) -> Result<TokenStream> {
+        let ident = &self.ident;
+        let parent_ident = self.get_parent_or()?.get_ident_or()?;
+
+        // Skipped variants can still be built by hand, so they need a handler like any other
+        let run = match (&self.run, self.flatten && !self.skip) {
+            (Some(run), _) => run,
+            (None, true) => &Override::Inherit,
+            (None, false) => {
+                return Err(Error::custom(
+                    "Every variant, skipped ones included, needs #[clap(run = \"...\")] or a delegating #[clap(run)]",
+                )
+                .with_span(&self.ident))
+            }
+        };
+
+        Ok(match run {
+            Override::Explicit(handler) => {
+                if self.fields.is_struct() {
+                    let idents = self
+                        .fields
+                        .iter()
+                        .map(|f| f.ident.clone())
+                        .collect::<Vec<_>>();
+
+                    quote! {
+                        #parent_ident::#ident { #(#idents),* } => #handler(clap_context, #(#idents),*),
+                    }
+                } else if self.fields.is_tuple() {
+                    let idents = (0..self.fields.len())
+                        .map(|i| format_ident!("clap_arg_{}", i))
+                        .collect::<Vec<_>>();
+
+                    quote! {
+                        #parent_ident::#ident(#(#idents),*) => #handler(clap_context, #(#idents),*),
+                    }
+                } else {
+                    quote! {
+                        #parent_ident::#ident => #handler(clap_context),
+                    }
+                }
+            }
+            Override::Inherit => {
+                if self.fields.is_newtype() {
+                    quote! {
+                        #parent_ident::#ident(clap_arg) => clap_derive_darling::Run::run(clap_arg, clap_context),
+                    }
+                } else {
+                    let mut subcommands =
+                        self.fields.iter().enumerate().filter(|(_, f)| f.subcommand);
+
+                    let (index, field) = match (subcommands.next(), subcommands.next()) {
+                        (Some(subcommand), None) => subcommand,
+                        _ => {
+                            return Err(Error::custom(
+                                "A delegating #[clap(run)] needs a newtype variant or exactly one subcommand field",
+                            )
+                            .with_span(&self.ident))
+                        }
+                    };
+
+                    if self.fields.is_struct() {
+                        let field_ident = &field.ident;
+
+                        quote! {
+                            #parent_ident::#ident { #field_ident, .. } => clap_derive_darling::Run::run(#field_ident, clap_context),
+                        }
+                    } else {
+                        let wildcards = (0..self.fields.len()).map(|i| {
+                            if i == index {
+                                quote! { clap_arg }
+                            } else {
+                                quote! { _ }
+                            }
+                        });
+
+                        quote! {
+                            #parent_ident::#ident(#(#wildcards),*) => clap_derive_darling::Run::run(clap_arg, clap_context),
+                        }
+                    }
+                }
+            }
+        })
     }
+
+    fn to_tokens_subcommand_path(&self) -> Result<TokenStream> {
+        let ident = &self.ident;
+        let parent_ident = self.get_parent_or()?.get_ident_or()?;
+
+        Ok(if self.skip {
+            quote! {
+                #parent_ident::#ident { .. } => Vec::new(),
+            }
+        } else if self.flatten {
+            let ty = self.get_flatten_type()?;
+
+            quote! {
+                #parent_ident::#ident(clap_arg) => <#ty as clap_derive_darling::Subcommand>::subcommand_path(clap_arg),
+            }
+        } else if self.external_subcommand {
+            let as_str = if self.is_external_subcommand_os()? {
+                quote! { .and_then(|s| s.to_str()) }
+            } else {
+                quote! { .map(|s| s.as_str()) }
+            };
+
+            quote! {
+                #parent_ident::#ident(clap_arg) => clap_arg.first()#as_str.into_iter().collect(),
+            }
+        } else if self.fields.is_newtype() {
+            let name = self.get_name_or()?;
+            let ty = &self.get_fieldstructs()[0].ty;
+
+            quote! {
+                #parent_ident::#ident(clap_arg) => {
+                    let mut clap_path = vec![#name];
+                    clap_path.extend(<#ty as clap_derive_darling::Args>::args_subcommand_path(clap_arg));
+                    clap_path
+                }
+            }
+        } else {
+            let name = self.get_name_or()?;
+
+            let fields = if self.fields.is_tuple() {
+                self.get_tuple_fieldstructs()?
+            } else {
+                self.get_fieldstructs()
+            };
+
+            let (bindings, paths): (Vec<_>, Vec<_>) = fields
+                .iter()
+                .filter(|f| f.skip.is_none() && (f.subcommand || f.flatten.is_some()))
+                .map(|f| {
+                    let ident = &f.ident;
+                    f.to_tokens_subcommand_path(quote! { #ident })
+                        .map(|path| (ident, path))
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .unzip();
+
+            let pattern = if self.fields.is_tuple() {
+                let wildcards = fields.iter().map(|f| {
+                    if bindings.contains(&&f.ident) {
+                        let ident = &f.ident;
+                        quote! { #ident }
+                    } else {
+                        quote! { _ }
+                    }
+                });
+
+                quote! { #parent_ident::#ident(#(#wildcards),*) }
+            } else {
+                quote! { #parent_ident::#ident { #(#bindings,)* .. } }
+            };
+
+            quote! {
+                #pattern => {
+                    #[allow(unused_mut)]
+                    let mut clap_path = vec![#name];
+                    #(#paths)*
+                    clap_path
+                }
+            }
+        })
+    }
+
     fn to_tokens_has_subcommand(&self) -> Result<TokenStream> {
-        let name = self.get_name_or()?;
+        let name_matches = self.to_tokens_name_matches()?;
 
         Ok(if self.skip {
             quote! {}
+        } else if self.flatten {
+            let ty = self.get_flatten_type()?;
+
+            quote! {
+                if <#ty as clap_derive_darling::Subcommand>::has_subcommand(clap_name) {
+                    return true;
+                }
+            }
+        } else if self.external_subcommand {
+            quote! {
+                return true;
+            }
         } else {
             quote! {
-                {
-                    let name = #name;
-                    if name == clap_name {
-                        return true;
-                    }
+                if #name_matches {
+                    return true;
                 }
             }
         })
@@ -451,7 +1275,13 @@ impl ClapFields for ClapSubcommandVariant {
         self.rename_all_value
     }
 }
-impl ClapFieldStructs for ClapSubcommandVariant {}
+impl ClapFieldStructs for ClapSubcommandVariant {
+    fn augment_field(&self, field: &mut ClapField) {
+        if self.env_scope {
+            field.env_scope = self.get_name();
+        }
+    }
+}
 impl ClapTraitImpls for ClapSubcommandVariant {}
 impl ClapParserArgsCommon for ClapSubcommandVariant {
     fn get_author(&self) -> Option<&Override<String>> {
@@ -15,6 +15,12 @@ enum Command {
         #[clap(long)]
         embedded: Option<String>,
     },
+    /// Unit about
+    #[clap(alias = "u", visible_alias = "un", short_flag = "U", hide, display_order = 1)]
+    Unit,
+    Copy(PathBuf, #[clap(name = "to")] PathBuf),
+    #[clap(flatten)]
+    Plugin(PluginCommand),
     #[clap(skip)]
     SkipMe,
     #[clap(external_subcommand)]
@@ -37,3 +43,63 @@ fn test() {
     )
     .unwrap();
 }
+
+#[test]
+fn test_tuple_field_name_error() {
+    let parsed = syn::parse_str(
+        r#"
+enum Command {
+    Bad((u8, u8), String),
+}
+"#,
+    )
+    .unwrap();
+    let subcommand = ClapSubcommand::from_derive_input(&parsed).unwrap();
+
+    let err = subcommand.to_tokens_result().unwrap_err();
+
+    assert!(err.to_string().contains("Cannot derive a name"));
+}
+
+#[test]
+fn test_env_scope_newtype_error() {
+    let parsed = syn::parse_str(
+        r#"
+#[clap(env_scope)]
+enum Command {
+    Deploy(DeployArgs),
+}
+"#,
+    )
+    .unwrap();
+    let subcommand = ClapSubcommand::from_derive_input(&parsed).unwrap();
+
+    let err = subcommand.to_tokens_result().unwrap_err();
+
+    assert!(err.to_string().contains("env_scope can't reach the args"));
+}
+
+#[test]
+fn test_env_scope_flatten_error() {
+    let parsed = syn::parse_str(
+        r#"
+#[clap(env_scope)]
+enum Command {
+    Deploy {
+        #[clap(long)]
+        zone: String,
+        #[clap(flatten)]
+        common: Common,
+    },
+}
+"#,
+    )
+    .unwrap();
+    let subcommand = ClapSubcommand::from_derive_input(&parsed).unwrap();
+
+    let err = subcommand.to_tokens_result().unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("env_scope can't reach flattened args"));
+}
@@ -0,0 +1,84 @@
+fn rar() {
+    let ___app = ___app.arg({
+        let ___name = "port";
+        let ___value = "PORT";
+        let ___long = "port";
+        clap::Arg::new(___name)
+            .long(___long)
+            .takes_value(true)
+            .value_name(___value)
+            .validator(|s: &str| -> Result<(), String> {
+                let value: Result<u16, _> = ::std::str::FromStr::from_str(s);
+                #[allow(unused_variables)]
+                let value = value.map_err(|err| err.to_string())?;
+                if !(1..=65535).contains(&value) {
+                    return Err(format!("value must be in the range {}", "1..=65535"));
+                }
+                Ok(())
+            })
+    });
+    let ___app = ___app.arg({
+        let ___name = "code";
+        let ___value = "CODE";
+        let ___long = "code";
+        clap::Arg::new(___name)
+            .long(___long)
+            .takes_value(true)
+            .value_name(___value)
+            .validator(|s: &str| -> Result<(), String> {
+                let value: Result<String, _> = ::std::str::FromStr::from_str(s);
+                #[allow(unused_variables)]
+                let value = value.map_err(|err| err.to_string())?;
+                if s.is_empty() {
+                    return Err(String::from("value must not be empty"));
+                }
+                if s.chars().count() < 1usize {
+                    return Err(format!("value must be at least {} characters long", 1usize));
+                }
+                if s.chars().count() > 8usize {
+                    return Err(format!("value must be at most {} characters long", 8usize));
+                }
+                Ok(())
+            })
+    });
+    let ___app = ___app.arg({
+        let ___name = "name";
+        let ___value = "NAME";
+        let ___long = "name";
+        clap::Arg::new(___name)
+            .long(___long)
+            .takes_value(true)
+            .value_name(___value)
+            .required(false)
+            .validator(|s: &str| -> Result<(), String> {
+                let value: Result<String, _> = ::std::str::FromStr::from_str(s);
+                #[allow(unused_variables)]
+                let value = value.map_err(|err| err.to_string())?;
+                static REGEX: clap_derive_darling::OnceBox<clap_derive_darling::regex::Regex> =
+                    clap_derive_darling::OnceBox::new();
+                let regex = REGEX.get_or_init(|| {
+                    Box::new(
+                        clap_derive_darling::regex::Regex::new("^[a-z]+$")
+                            .expect("regex was checked by the derive"),
+                    )
+                });
+                if !regex.is_match(s) {
+                    return Err(format!("value must match the pattern {}", "^[a-z]+$"));
+                }
+                Ok(())
+            })
+    });
+    let ___app = ___app.arg({
+        let ___name = "tag";
+        let ___value = "TAG";
+        let ___long = "tag";
+        clap::Arg::new(___name)
+            .long(___long)
+            .takes_value(true)
+            .value_name(___value)
+            .required(true)
+            .multiple_occurrences(true)
+            .max_occurrences(3usize)
+            .validator(|s| ::std::str::FromStr::from_str(s).map(|_: String| ()))
+    });
+}
@@ -34,33 +34,33 @@ impl clap_derive_darling::Args for Application {
             let ___name = "opt-arg-enum";
             let ___value = "OPT_ARG_ENUM";
             let ___long = "opt-arg-enum";
-            clap::Arg::new(___name)
-                .long(___long)
-                .takes_value(true)
-                .value_name(___value)
-                .required(false)
-                .possible_values(
-                    <MyArgEnum as clap_derive_darling::ArgEnum>::value_variants()
-                        .iter()
-                        .filter_map(clap_derive_darling::ArgEnum::to_possible_value),
-                )
+            clap_derive_darling::arg_enum_values::<MyArgEnum>(
+                clap::Arg::new(___name)
+                    .long(___long)
+                    .takes_value(true)
+                    .value_name(___value)
+                    .required(false)
+                    .long_help(<MyArgEnum as clap_derive_darling::ArgEnum>::long_help())
+                    .ignore_case(<MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case()),
+                Vec::new(),
+            )
         });
         let ___app = ___app.arg({
             let ___name = "opt-opt-arg-enum";
             let ___value = "OPT_OPT_ARG_ENUM";
             let ___long = "opt-opt-arg-enum";
-            clap::Arg::new(___name)
-                .long(___long)
-                .takes_value(true)
-                .value_name(___value)
-                .required(false)
-                .min_values(0)
-                .max_values(1)
-                .possible_values(
-                    <MyArgEnum as clap_derive_darling::ArgEnum>::value_variants()
-                        .iter()
-                        .filter_map(clap_derive_darling::ArgEnum::to_possible_value),
-                )
+            clap_derive_darling::arg_enum_values::<MyArgEnum>(
+                clap::Arg::new(___name)
+                    .long(___long)
+                    .takes_value(true)
+                    .value_name(___value)
+                    .required(false)
+                    .min_values(0)
+                    .max_values(1)
+                    .long_help(<MyArgEnum as clap_derive_darling::ArgEnum>::long_help())
+                    .ignore_case(<MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case()),
+                Vec::new(),
+            )
         });
         let ___app = ___app.arg({
             let ___name = "bool";
@@ -102,13 +102,19 @@ impl clap_derive_darling::Args for Application {
                 .value_name(___value)
                 .required(false)
                 .multiple_occurrences(true)
+                .min_values(0)
+                .number_of_values(1)
                 .validator(|s| ::std::str::FromStr::from_str(s).map(|_: String| ()))
         });
         let ___app = <Command as clap_derive_darling::Subcommand>::augment_subcommands(
             ___app,
             ___prefix.clone(),
         );
-        let ___app = ___app.setting(clap::AppSettings::SubcommandRequiredElseHelp);
+        let ___app = if <Command as clap_derive_darling::Subcommand>::has_default_subcommand() {
+            ___app
+        } else {
+            ___app.setting(clap::AppSettings::SubcommandRequiredElseHelp)
+        };
         ___app
     }
     fn augment_args_for_update<'a>(
@@ -149,33 +155,33 @@ impl clap_derive_darling::Args for Application {
             let ___name = "opt-arg-enum";
             let ___value = "OPT_ARG_ENUM";
             let ___long = "opt-arg-enum";
-            clap::Arg::new(___name)
-                .long(___long)
-                .takes_value(true)
-                .value_name(___value)
-                .required(false)
-                .possible_values(
-                    <MyArgEnum as clap_derive_darling::ArgEnum>::value_variants()
-                        .iter()
-                        .filter_map(clap_derive_darling::ArgEnum::to_possible_value),
-                )
+            clap_derive_darling::arg_enum_values::<MyArgEnum>(
+                clap::Arg::new(___name)
+                    .long(___long)
+                    .takes_value(true)
+                    .value_name(___value)
+                    .required(false)
+                    .long_help(<MyArgEnum as clap_derive_darling::ArgEnum>::long_help())
+                    .ignore_case(<MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case()),
+                Vec::new(),
+            )
         });
         let ___app = ___app.arg({
             let ___name = "opt-opt-arg-enum";
             let ___value = "OPT_OPT_ARG_ENUM";
             let ___long = "opt-opt-arg-enum";
-            clap::Arg::new(___name)
-                .long(___long)
-                .takes_value(true)
-                .value_name(___value)
-                .required(false)
-                .min_values(0)
-                .max_values(1)
-                .possible_values(
-                    <MyArgEnum as clap_derive_darling::ArgEnum>::value_variants()
-                        .iter()
-                        .filter_map(clap_derive_darling::ArgEnum::to_possible_value),
-                )
+            clap_derive_darling::arg_enum_values::<MyArgEnum>(
+                clap::Arg::new(___name)
+                    .long(___long)
+                    .takes_value(true)
+                    .value_name(___value)
+                    .required(false)
+                    .min_values(0)
+                    .max_values(1)
+                    .long_help(<MyArgEnum as clap_derive_darling::ArgEnum>::long_help())
+                    .ignore_case(<MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case()),
+                Vec::new(),
+            )
         });
         let ___app = ___app.arg({
             let ___name = "bool";
@@ -217,15 +223,30 @@ impl clap_derive_darling::Args for Application {
                 .value_name(___value)
                 .required(false)
                 .multiple_occurrences(true)
+                .min_values(0)
+                .number_of_values(1)
                 .validator(|s| ::std::str::FromStr::from_str(s).map(|_: String| ()))
         });
         let ___app = <Command as clap_derive_darling::Subcommand>::augment_subcommands(
             ___app,
             ___prefix.clone(),
         );
-        let ___app = ___app.setting(clap::AppSettings::SubcommandRequiredElseHelp);
+        let ___app = if <Command as clap_derive_darling::Subcommand>::has_default_subcommand() {
+            ___app
+        } else {
+            ___app.setting(clap::AppSettings::SubcommandRequiredElseHelp)
+        };
         ___app
     }
+    fn args_subcommand_path(&self) -> Vec<&str> {
+        #[allow(unused_mut)]
+        let mut clap_path = Vec::new();
+        clap_path
+            .extend(<Flatten as clap_derive_darling::Args>::args_subcommand_path(&self.flatten));
+        clap_path
+            .extend(<Command as clap_derive_darling::Subcommand>::subcommand_path(&self.command));
+        clap_path
+    }
 }
 impl clap_derive_darling::FromArgMatches for Application {
     fn from_arg_matches(
@@ -235,13 +256,17 @@ impl clap_derive_darling::FromArgMatches for Application {
         let v = Application {
             name: {
                 let ___name = "name";
+                let ___flag = {
+                    let ___long = "name";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .value_of(&___name)
                     .map(|s| {
                         ::std::str::FromStr::from_str(s).map_err(|err| {
                             clap::Error::raw(
                                 clap::ErrorKind::ValueValidation,
-                                format!("Invalid value for {}: {}", &___name, &err),
+                                format!("Invalid value for {}: {}", &___flag, &err),
                             )
                         })
                     })
@@ -249,58 +274,77 @@ impl clap_derive_darling::FromArgMatches for Application {
                     .ok_or_else(|| {
                         clap::Error::raw(
                             clap::ErrorKind::ValueValidation,
-                            format!("Invalid value for {}", &___name),
+                            format!("Invalid value for {}", &___flag),
                         )
                     })?
             },
             option: {
                 let ___name = "option";
+                let ___flag = {
+                    let ___long = "option";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .value_of(&___name)
                     .map(|s| {
                         ::std::str::FromStr::from_str(s).map_err(|err| {
                             clap::Error::raw(
                                 clap::ErrorKind::ValueValidation,
-                                format!("Invalid value for {}: {}", &___name, &err),
+                                format!("Invalid value for {}: {}", &___flag, &err),
                             )
                         })
                     })
                     .transpose()?
             },
             flatten: {
-                clap_derive_darling::FromArgMatches::from_arg_matches(___arg_matches, ___prefix)
-                    .unwrap()
+                clap_derive_darling::FromArgMatches::from_arg_matches(
+                    ___arg_matches,
+                    ___prefix.clone(),
+                )?
             },
             opt_arg_enum: {
                 let ___name = "opt-arg-enum";
+                let ___flag = {
+                    let ___long = "opt-arg-enum";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .value_of(&___name)
                     .map(|s| {
-                        <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(s, false).map_err(
-                            |err| {
-                                clap::Error::raw(
-                                    clap::ErrorKind::ValueValidation,
-                                    format!("Invalid value for {}: {}", &___name, &err),
-                                )
-                            },
+                        <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(
+                            s,
+                            <MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case(),
                         )
+                        .map_err(|err| {
+                            clap::Error::raw(
+                                clap::ErrorKind::ValueValidation,
+                                format!("Invalid value for {}: {}", &___flag, &err),
+                            )
+                        })
                     })
                     .transpose()?
             },
             opt_opt_arg_enum: {
                 let ___name = "opt-opt-arg-enum";
+                let ___flag = {
+                    let ___long = "opt-opt-arg-enum";
+                    format!("--{}", ___long)
+                };
                 if ___arg_matches.is_present(&___name) {
                     Some(
                         ___arg_matches
                             .value_of(&___name)
                             .map(|s| {
-                                <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(s, false)
-                                    .map_err(|err| {
-                                        clap::Error::raw(
-                                            clap::ErrorKind::ValueValidation,
-                                            format!("Invalid value for {}: {}", &___name, &err),
-                                        )
-                                    })
+                                <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(
+                                    s,
+                                    <MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case(),
+                                )
+                                .map_err(|err| {
+                                    clap::Error::raw(
+                                        clap::ErrorKind::ValueValidation,
+                                        format!("Invalid value for {}: {}", &___flag, &err),
+                                    )
+                                })
                             })
                             .transpose()?,
                     )
@@ -314,6 +358,10 @@ impl clap_derive_darling::FromArgMatches for Application {
             },
             opt_opt_t: {
                 let ___name = "opt-opt-t";
+                let ___flag = {
+                    let ___long = "opt-opt-t";
+                    format!("--{}", ___long)
+                };
                 if ___arg_matches.is_present(&___name) {
                     Some(
                         ___arg_matches
@@ -322,7 +370,7 @@ impl clap_derive_darling::FromArgMatches for Application {
                                 ::std::str::FromStr::from_str(s).map_err(|err| {
                                     clap::Error::raw(
                                         clap::ErrorKind::ValueValidation,
-                                        format!("Invalid value for {}: {}", &___name, &err),
+                                        format!("Invalid value for {}: {}", &___flag, &err),
                                     )
                                 })
                             })
@@ -334,6 +382,10 @@ impl clap_derive_darling::FromArgMatches for Application {
             },
             vec_str: {
                 let ___name = "vec-str";
+                let ___flag = {
+                    let ___long = "vec-str";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .values_of(&___name)
                     .map(|v| {
@@ -341,30 +393,40 @@ impl clap_derive_darling::FromArgMatches for Application {
                             ::std::str::FromStr::from_str(s).map_err(|err| {
                                 clap::Error::raw(
                                     clap::ErrorKind::ValueValidation,
-                                    format!("Invalid value for {}: {}", &___name, &err),
+                                    format!("Invalid value for {}: {}", &___flag, &err),
                                 )
                             })
                         })
                     })
-                    .map(|v| v.collect::<Result<Vec<_>, _>>())
-                    .unwrap_or_else(|| Ok(Vec::new()))?
+                    .map(|v| v.collect::<Result<_, _>>())
+                    .unwrap_or_else(|| Ok(Default::default()))?
             },
             opt_vec_str: {
                 let ___name = "opt-vec-str";
-                ___arg_matches
-                    .values_of(&___name)
-                    .map(|v| {
-                        v.map(|s| {
-                            ::std::str::FromStr::from_str(s).map_err(|err| {
-                                clap::Error::raw(
-                                    clap::ErrorKind::ValueValidation,
-                                    format!("Invalid value for {}: {}", &___name, &err),
-                                )
+                let ___flag = {
+                    let ___long = "opt-vec-str";
+                    format!("--{}", ___long)
+                };
+                if ___arg_matches.is_present(&___name) {
+                    Some(
+                        ___arg_matches
+                            .values_of(&___name)
+                            .map(|v| {
+                                v.map(|s| {
+                                    ::std::str::FromStr::from_str(s).map_err(|err| {
+                                        clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: {}", &___flag, &err),
+                                        )
+                                    })
+                                })
                             })
-                        })
-                    })
-                    .map(|v| v.collect::<Result<Vec<_>, _>>())
-                    .transpose()?
+                            .map(|v| v.collect::<Result<_, _>>())
+                            .unwrap_or_else(|| Ok(Default::default()))?,
+                    )
+                } else {
+                    None
+                }
             },
             command: <Command as clap_derive_darling::FromArgMatches>::from_arg_matches(
                 ___arg_matches,
@@ -383,13 +445,17 @@ impl clap_derive_darling::FromArgMatches for Application {
             let name = &mut self.name;
             *name = {
                 let ___name = "name";
+                let ___flag = {
+                    let ___long = "name";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .value_of(&___name)
                     .map(|s| {
                         ::std::str::FromStr::from_str(s).map_err(|err| {
                             clap::Error::raw(
                                 clap::ErrorKind::ValueValidation,
-                                format!("Invalid value for {}: {}", &___name, &err),
+                                format!("Invalid value for {}: {}", &___flag, &err),
                             )
                         })
                     })
@@ -397,7 +463,7 @@ impl clap_derive_darling::FromArgMatches for Application {
                     .ok_or_else(|| {
                         clap::Error::raw(
                             clap::ErrorKind::ValueValidation,
-                            format!("Invalid value for {}", &___name),
+                            format!("Invalid value for {}", &___flag),
                         )
                     })?
             };
@@ -407,13 +473,17 @@ impl clap_derive_darling::FromArgMatches for Application {
             let option = &mut self.option;
             *option = {
                 let ___name = "option";
+                let ___flag = {
+                    let ___long = "option";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .value_of(&___name)
                     .map(|s| {
                         ::std::str::FromStr::from_str(s).map_err(|err| {
                             clap::Error::raw(
                                 clap::ErrorKind::ValueValidation,
-                                format!("Invalid value for {}: {}", &___name, &err),
+                                format!("Invalid value for {}: {}", &___flag, &err),
                             )
                         })
                     })
@@ -427,8 +497,8 @@ impl clap_derive_darling::FromArgMatches for Application {
                 clap_derive_darling::FromArgMatches::update_from_arg_matches(
                     flatten,
                     ___arg_matches,
-                    ___prefix,
-                )
+                    ___prefix.clone(),
+                )?
             };
         }
         {
@@ -436,17 +506,23 @@ impl clap_derive_darling::FromArgMatches for Application {
             let opt_arg_enum = &mut self.opt_arg_enum;
             *opt_arg_enum = {
                 let ___name = "opt-arg-enum";
+                let ___flag = {
+                    let ___long = "opt-arg-enum";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .value_of(&___name)
                     .map(|s| {
-                        <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(s, false).map_err(
-                            |err| {
-                                clap::Error::raw(
-                                    clap::ErrorKind::ValueValidation,
-                                    format!("Invalid value for {}: {}", &___name, &err),
-                                )
-                            },
+                        <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(
+                            s,
+                            <MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case(),
                         )
+                        .map_err(|err| {
+                            clap::Error::raw(
+                                clap::ErrorKind::ValueValidation,
+                                format!("Invalid value for {}: {}", &___flag, &err),
+                            )
+                        })
                     })
                     .transpose()?
             };
@@ -456,18 +532,25 @@ impl clap_derive_darling::FromArgMatches for Application {
             let opt_opt_arg_enum = &mut self.opt_opt_arg_enum;
             *opt_opt_arg_enum = {
                 let ___name = "opt-opt-arg-enum";
+                let ___flag = {
+                    let ___long = "opt-opt-arg-enum";
+                    format!("--{}", ___long)
+                };
                 if ___arg_matches.is_present(&___name) {
                     Some(
                         ___arg_matches
                             .value_of(&___name)
                             .map(|s| {
-                                <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(s, false)
-                                    .map_err(|err| {
-                                        clap::Error::raw(
-                                            clap::ErrorKind::ValueValidation,
-                                            format!("Invalid value for {}: {}", &___name, &err),
-                                        )
-                                    })
+                                <MyArgEnum as clap_derive_darling::ArgEnum>::from_str(
+                                    s,
+                                    <MyArgEnum as clap_derive_darling::ArgEnum>::ignore_case(),
+                                )
+                                .map_err(|err| {
+                                    clap::Error::raw(
+                                        clap::ErrorKind::ValueValidation,
+                                        format!("Invalid value for {}: {}", &___flag, &err),
+                                    )
+                                })
                             })
                             .transpose()?,
                     )
@@ -489,6 +572,10 @@ impl clap_derive_darling::FromArgMatches for Application {
             let opt_opt_t = &mut self.opt_opt_t;
             *opt_opt_t = {
                 let ___name = "opt-opt-t";
+                let ___flag = {
+                    let ___long = "opt-opt-t";
+                    format!("--{}", ___long)
+                };
                 if ___arg_matches.is_present(&___name) {
                     Some(
                         ___arg_matches
@@ -497,7 +584,7 @@ impl clap_derive_darling::FromArgMatches for Application {
                                 ::std::str::FromStr::from_str(s).map_err(|err| {
                                     clap::Error::raw(
                                         clap::ErrorKind::ValueValidation,
-                                        format!("Invalid value for {}: {}", &___name, &err),
+                                        format!("Invalid value for {}: {}", &___flag, &err),
                                     )
                                 })
                             })
@@ -513,6 +600,10 @@ impl clap_derive_darling::FromArgMatches for Application {
             let vec_str = &mut self.vec_str;
             *vec_str = {
                 let ___name = "vec-str";
+                let ___flag = {
+                    let ___long = "vec-str";
+                    format!("--{}", ___long)
+                };
                 ___arg_matches
                     .values_of(&___name)
                     .map(|v| {
@@ -520,13 +611,13 @@ impl clap_derive_darling::FromArgMatches for Application {
                             ::std::str::FromStr::from_str(s).map_err(|err| {
                                 clap::Error::raw(
                                     clap::ErrorKind::ValueValidation,
-                                    format!("Invalid value for {}: {}", &___name, &err),
+                                    format!("Invalid value for {}: {}", &___flag, &err),
                                 )
                             })
                         })
                     })
-                    .map(|v| v.collect::<Result<Vec<_>, _>>())
-                    .unwrap_or_else(|| Ok(Vec::new()))?
+                    .map(|v| v.collect::<Result<_, _>>())
+                    .unwrap_or_else(|| Ok(Default::default()))?
             };
         }
         {
@@ -534,20 +625,30 @@ impl clap_derive_darling::FromArgMatches for Application {
             let opt_vec_str = &mut self.opt_vec_str;
             *opt_vec_str = {
                 let ___name = "opt-vec-str";
-                ___arg_matches
-                    .values_of(&___name)
-                    .map(|v| {
-                        v.map(|s| {
-                            ::std::str::FromStr::from_str(s).map_err(|err| {
-                                clap::Error::raw(
-                                    clap::ErrorKind::ValueValidation,
-                                    format!("Invalid value for {}: {}", &___name, &err),
-                                )
+                let ___flag = {
+                    let ___long = "opt-vec-str";
+                    format!("--{}", ___long)
+                };
+                if ___arg_matches.is_present(&___name) {
+                    Some(
+                        ___arg_matches
+                            .values_of(&___name)
+                            .map(|v| {
+                                v.map(|s| {
+                                    ::std::str::FromStr::from_str(s).map_err(|err| {
+                                        clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: {}", &___flag, &err),
+                                        )
+                                    })
+                                })
                             })
-                        })
-                    })
-                    .map(|v| v.collect::<Result<Vec<_>, _>>())
-                    .transpose()?
+                            .map(|v| v.collect::<Result<_, _>>())
+                            .unwrap_or_else(|| Ok(Default::default()))?,
+                    )
+                } else {
+                    None
+                }
             };
         }
         {
@@ -572,4 +673,8 @@ impl clap::IntoApp for Application {
         <Self as clap_derive_darling::Args>::augment_args_for_update(___app, Vec::new())
     }
 }
-impl clap_derive_darling::Clap for Application {}
+impl clap_derive_darling::Clap for Application {
+    fn subcommand_path(&self) -> Vec<&str> {
+        <Self as clap_derive_darling::Args>::args_subcommand_path(self)
+    }
+}
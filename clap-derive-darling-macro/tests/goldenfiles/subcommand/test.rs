@@ -10,21 +10,27 @@ impl clap_derive_darling::FromArgMatches for Command {
                     return Ok(Command::First(
                         <FirstCommand as clap_derive_darling::FromArgMatches>::from_arg_matches(
                             ___arg_matches,
-                            ___prefix,
+                            ___prefix.clone(),
                         )?,
                     ));
                 }
                 if "2nd" == clap_name {
+                    #[allow(unused_variables)]
+                    let ___prefix: Vec<&'static str> = ___prefix.clone();
                     return Ok(Command::Second {
                         embedded: {
                             let ___name = "embedded";
+                            let ___flag = {
+                                let ___long = "embedded";
+                                format!("--{}", ___long)
+                            };
                             ___arg_matches
                                 .value_of(&___name)
                                 .map(|s| {
                                     ::std::str::FromStr::from_str(s).map_err(|err| {
                                         clap::Error::raw(
                                             clap::ErrorKind::ValueValidation,
-                                            format!("Invalid value for {}: {}", &___name, &err),
+                                            format!("Invalid value for {}: {}", &___flag, &err),
                                         )
                                     })
                                 })
@@ -32,24 +38,86 @@ impl clap_derive_darling::FromArgMatches for Command {
                         },
                     });
                 }
-                if "external" == clap_name {
-                    return Ok(Command::External(
-                        ::std::iter::once(::std::string::String::from(clap_name))
-                            .chain(
-                                ___arg_matches
-                                    .values_of("")
-                                    .into_iter()
-                                    .flatten()
-                                    .map(::std::string::String::from),
-                            )
-                            .collect::<Vec<_>>(),
+                if ["unit", "u", "un"].contains(&clap_name) {
+                    return Ok(Command::Unit);
+                }
+                if "copy" == clap_name {
+                    #[allow(unused_variables)]
+                    let ___prefix: Vec<&'static str> = ___prefix.clone();
+                    return Ok(Command::Copy(
+                        {
+                            let ___name = "path-buf";
+                            let ___flag = {
+                                let ___value = "PATH_BUF";
+                                format!("<{}>", ___value)
+                            };
+                            ___arg_matches
+                                .value_of(&___name)
+                                .map(|s| {
+                                    ::std::str::FromStr::from_str(s).map_err(|err| {
+                                        clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: {}", &___flag, &err),
+                                        )
+                                    })
+                                })
+                                .transpose()?
+                                .ok_or_else(|| {
+                                    clap::Error::raw(
+                                        clap::ErrorKind::ValueValidation,
+                                        format!("Invalid value for {}", &___flag),
+                                    )
+                                })?
+                        },
+                        {
+                            let ___name = "to";
+                            let ___flag = {
+                                let ___value = "TO";
+                                format!("<{}>", ___value)
+                            };
+                            ___arg_matches
+                                .value_of(&___name)
+                                .map(|s| {
+                                    ::std::str::FromStr::from_str(s).map_err(|err| {
+                                        clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: {}", &___flag, &err),
+                                        )
+                                    })
+                                })
+                                .transpose()?
+                                .ok_or_else(|| {
+                                    clap::Error::raw(
+                                        clap::ErrorKind::ValueValidation,
+                                        format!("Invalid value for {}", &___flag),
+                                    )
+                                })?
+                        },
                     ));
                 }
             }
-            Err(clap::Error::raw(
-                clap::ErrorKind::UnrecognizedSubcommand,
-                format!("The subcommand '{}' watn't recognized", clap_name),
-            ))
+            if <PluginCommand as clap_derive_darling::Subcommand>::has_subcommand(clap_name) {
+                return Ok(Command::Plugin(
+                    <PluginCommand as clap_derive_darling::FromArgMatches>::from_arg_matches(
+                        ___arg_matches,
+                        ___prefix.clone(),
+                    )?,
+                ));
+            }
+            {
+                let ___arg_matches = sub_arg_matches;
+                return Ok(Command::External(
+                    ::std::iter::once(::std::string::String::from(clap_name))
+                        .chain(
+                            ___arg_matches
+                                .values_of("")
+                                .into_iter()
+                                .flatten()
+                                .map(::std::string::String::from),
+                        )
+                        .collect::<Vec<_>>(),
+                ));
+            }
         } else {
             Err(clap::Error::raw(
                 clap::ErrorKind::MissingSubcommand,
@@ -69,21 +137,27 @@ impl clap_derive_darling::FromArgMatches for Command {
                     clap_derive_darling::FromArgMatches::update_from_arg_matches(
                         clap_arg,
                         sub_arg_matches,
-                        ___prefix,
+                        ___prefix.clone(),
                     )?
                 }
                 Command::Second { ref mut embedded } if "2nd" == clap_name => {
                     let ___arg_matches = sub_arg_matches;
+                    #[allow(unused_variables)]
+                    let ___prefix: Vec<&'static str> = ___prefix.clone();
                     {
                         *embedded = {
                             let ___name = "embedded";
+                            let ___flag = {
+                                let ___long = "embedded";
+                                format!("--{}", ___long)
+                            };
                             ___arg_matches
                                 .value_of(&___name)
                                 .map(|s| {
                                     ::std::str::FromStr::from_str(s).map_err(|err| {
                                         clap::Error::raw(
                                             clap::ErrorKind::ValueValidation,
-                                            format!("Invalid value for {}: {}", &___name, &err),
+                                            format!("Invalid value for {}: {}", &___flag, &err),
                                         )
                                     })
                                 })
@@ -91,16 +165,72 @@ impl clap_derive_darling::FromArgMatches for Command {
                         };
                     }
                 }
-                Command::External(ref mut clap_arg) if "external" == clap_name => {
-                    *clap_arg = ::std::iter::once(::std::string::String::from(clap_name))
-                        .chain(
+                Command::Unit if ["unit", "u", "un"].contains(&clap_name) => {}
+                Command::Copy(ref mut clap_arg_0, ref mut clap_arg_1) if "copy" == clap_name => {
+                    let ___arg_matches = sub_arg_matches;
+                    #[allow(unused_variables)]
+                    let ___prefix: Vec<&'static str> = ___prefix.clone();
+                    {
+                        *clap_arg_0 = {
+                            let ___name = "path-buf";
+                            let ___flag = {
+                                let ___value = "PATH_BUF";
+                                format!("<{}>", ___value)
+                            };
                             ___arg_matches
-                                .values_of("")
-                                .into_iter()
-                                .flatten()
-                                .map(::std::string::String::from),
-                        )
-                        .collect::<Vec<_>>();
+                                .value_of(&___name)
+                                .map(|s| {
+                                    ::std::str::FromStr::from_str(s).map_err(|err| {
+                                        clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: {}", &___flag, &err),
+                                        )
+                                    })
+                                })
+                                .transpose()?
+                                .ok_or_else(|| {
+                                    clap::Error::raw(
+                                        clap::ErrorKind::ValueValidation,
+                                        format!("Invalid value for {}", &___flag),
+                                    )
+                                })?
+                        };
+                        *clap_arg_1 = {
+                            let ___name = "to";
+                            let ___flag = {
+                                let ___value = "TO";
+                                format!("<{}>", ___value)
+                            };
+                            ___arg_matches
+                                .value_of(&___name)
+                                .map(|s| {
+                                    ::std::str::FromStr::from_str(s).map_err(|err| {
+                                        clap::Error::raw(
+                                            clap::ErrorKind::ValueValidation,
+                                            format!("Invalid value for {}: {}", &___flag, &err),
+                                        )
+                                    })
+                                })
+                                .transpose()?
+                                .ok_or_else(|| {
+                                    clap::Error::raw(
+                                        clap::ErrorKind::ValueValidation,
+                                        format!("Invalid value for {}", &___flag),
+                                    )
+                                })?
+                        };
+                    }
+                }
+                Command::Plugin(ref mut clap_arg)
+                    if <PluginCommand as clap_derive_darling::Subcommand>::has_subcommand(
+                        clap_name,
+                    ) =>
+                {
+                    clap_derive_darling::FromArgMatches::update_from_arg_matches(
+                        clap_arg,
+                        ___arg_matches,
+                        ___prefix.clone(),
+                    )?
                 }
                 s => {
                     *s = <Self as clap_derive_darling::FromArgMatches>::from_arg_matches(
@@ -114,6 +244,7 @@ impl clap_derive_darling::FromArgMatches for Command {
     }
 }
 impl clap_derive_darling::Subcommand for Command {
+    const HAS_EXTERNAL_SUBCOMMAND: bool = true;
     fn augment_subcommands<'b>(
         ___app: clap::App<'b>,
         ___prefix: Vec<&'static str>,
@@ -123,7 +254,7 @@ impl clap_derive_darling::Subcommand for Command {
             let clap_subcommand = {
                 <FirstCommand as clap_derive_darling::Args>::augment_args(
                     clap_subcommand,
-                    Vec::new(),
+                    ___prefix.clone(),
                 )
             };
             clap_subcommand
@@ -132,6 +263,8 @@ impl clap_derive_darling::Subcommand for Command {
             let clap_subcommand = clap::App::new("2nd");
             {
                 let ___app = clap_subcommand;
+                #[allow(unused_variables)]
+                let ___prefix: Vec<&'static str> = ___prefix.clone();
                 let ___app = ___app.arg({
                     let ___name = "embedded";
                     let ___value = "EMBEDDED";
@@ -147,9 +280,48 @@ impl clap_derive_darling::Subcommand for Command {
             }
         });
         let ___app = ___app.subcommand({
-            let clap_subcommand = clap::App::new("external");
+            let clap_subcommand = clap::App::new("unit");
             clap_subcommand
+                .alias("u")
+                .visible_alias("un")
+                .short_flag('U')
+                .hide(true)
+                .display_order(1usize)
+                .about("Unit about")
         });
+        let ___app = ___app.subcommand({
+            let clap_subcommand = clap::App::new("copy");
+            {
+                let ___app = clap_subcommand;
+                #[allow(unused_variables)]
+                let ___prefix: Vec<&'static str> = ___prefix.clone();
+                let ___app = ___app.arg({
+                    let ___name = "path-buf";
+                    let ___value = "PATH_BUF";
+                    clap::Arg::new(___name)
+                        .takes_value(true)
+                        .value_name(___value)
+                        .validator(|s| ::std::str::FromStr::from_str(s).map(|_: PathBuf| ()))
+                });
+                let ___app = ___app.arg({
+                    let ___name = "to";
+                    let ___value = "TO";
+                    clap::Arg::new(___name)
+                        .takes_value(true)
+                        .value_name(___value)
+                        .validator(|s| ::std::str::FromStr::from_str(s).map(|_: PathBuf| ()))
+                });
+                ___app
+            }
+        });
+        const _: () = assert!(
+            !<PluginCommand as clap_derive_darling::Subcommand>::HAS_EXTERNAL_SUBCOMMAND,
+            "flatten can't be used on a subcommand enum with an external_subcommand",
+        );
+        let ___app = <PluginCommand as clap_derive_darling::Subcommand>::augment_subcommands(
+            ___app,
+            ___prefix.clone(),
+        );
         let ___app = ___app.setting(clap::AppSettings::AllowExternalSubcommands);
         ___app
     }
@@ -160,9 +332,9 @@ impl clap_derive_darling::Subcommand for Command {
         let ___app = ___app.subcommand({
             let clap_subcommand = clap::App::new("first");
             let clap_subcommand = {
-                <FirstCommand as clap_derive_darling::Args>::augment_args(
+                <FirstCommand as clap_derive_darling::Args>::augment_args_for_update(
                     clap_subcommand,
-                    Vec::new(),
+                    ___prefix.clone(),
                 )
             };
             clap_subcommand
@@ -171,6 +343,8 @@ impl clap_derive_darling::Subcommand for Command {
             let clap_subcommand = clap::App::new("2nd");
             {
                 let ___app = clap_subcommand;
+                #[allow(unused_variables)]
+                let ___prefix: Vec<&'static str> = ___prefix.clone();
                 let ___app = ___app.arg({
                     let ___name = "embedded";
                     let ___value = "EMBEDDED";
@@ -186,31 +360,97 @@ impl clap_derive_darling::Subcommand for Command {
             }
         });
         let ___app = ___app.subcommand({
-            let clap_subcommand = clap::App::new("external");
+            let clap_subcommand = clap::App::new("unit");
             clap_subcommand
+                .alias("u")
+                .visible_alias("un")
+                .short_flag('U')
+                .hide(true)
+                .display_order(1usize)
+                .about("Unit about")
+        });
+        let ___app = ___app.subcommand({
+            let clap_subcommand = clap::App::new("copy");
+            {
+                let ___app = clap_subcommand;
+                #[allow(unused_variables)]
+                let ___prefix: Vec<&'static str> = ___prefix.clone();
+                let ___app = ___app.arg({
+                    let ___name = "path-buf";
+                    let ___value = "PATH_BUF";
+                    clap::Arg::new(___name)
+                        .takes_value(true)
+                        .value_name(___value)
+                        .validator(|s| ::std::str::FromStr::from_str(s).map(|_: PathBuf| ()))
+                });
+                let ___app = ___app.arg({
+                    let ___name = "to";
+                    let ___value = "TO";
+                    clap::Arg::new(___name)
+                        .takes_value(true)
+                        .value_name(___value)
+                        .validator(|s| ::std::str::FromStr::from_str(s).map(|_: PathBuf| ()))
+                });
+                ___app
+            }
         });
+        let ___app =
+            <PluginCommand as clap_derive_darling::Subcommand>::augment_subcommands_for_update(
+                ___app,
+                ___prefix.clone(),
+            );
         let ___app = ___app.setting(clap::AppSettings::AllowExternalSubcommands);
         ___app
     }
     fn has_subcommand(clap_name: &str) -> bool {
-        {
-            let name = "first";
-            if name == clap_name {
-                return true;
-            }
+        if "first" == clap_name {
+            return true;
         }
-        {
-            let name = "2nd";
-            if name == clap_name {
-                return true;
-            }
+        if "2nd" == clap_name {
+            return true;
+        }
+        if ["unit", "u", "un"].contains(&clap_name) {
+            return true;
+        }
+        if "copy" == clap_name {
+            return true;
         }
-        {
-            let name = "external";
-            if name == clap_name {
-                return true;
+        if <PluginCommand as clap_derive_darling::Subcommand>::has_subcommand(clap_name) {
+            return true;
+        }
+        return true;
+    }
+    fn subcommand_path(&self) -> Vec<&str> {
+        match self {
+            Command::First(clap_arg) => {
+                let mut clap_path = vec!["first"];
+                clap_path.extend(
+                    <FirstCommand as clap_derive_darling::Args>::args_subcommand_path(clap_arg),
+                );
+                clap_path
+            }
+            Command::Second { .. } => {
+                #[allow(unused_mut)]
+                let mut clap_path = vec!["2nd"];
+                clap_path
+            }
+            Command::Unit { .. } => {
+                #[allow(unused_mut)]
+                let mut clap_path = vec!["unit"];
+                clap_path
+            }
+            Command::Copy(_, _) => {
+                #[allow(unused_mut)]
+                let mut clap_path = vec!["copy"];
+                clap_path
+            }
+            Command::Plugin(clap_arg) => {
+                <PluginCommand as clap_derive_darling::Subcommand>::subcommand_path(clap_arg)
+            }
+            Command::SkipMe { .. } => Vec::new(),
+            Command::External(clap_arg) => {
+                clap_arg.first().map(|s| s.as_str()).into_iter().collect()
             }
         }
-        false
     }
 }
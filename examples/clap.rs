@@ -45,6 +45,7 @@ struct Application {
     author = "Test Author",
     help_heading = "Other heading"
 )]
+#[allow(dead_code)]
 struct Flatten {
     #[clap(skip)]
     other: u64,
@@ -60,6 +61,7 @@ enum MyArgEnum {
 }
 
 #[derive(Subcommand, Debug)]
+#[allow(dead_code)]
 enum Command {
     First(FirstCommand),
     #[clap(name = "2nd", version = "9.9.9", help_heading = "Rar")]
@@ -76,6 +76,7 @@ enum MyArgEnum {
 }
 
 #[derive(Subcommand, Debug)]
+#[allow(dead_code)]
 enum Command {
     First(FirstCommand),
     #[clap(name = "2nd", version = "9.9.9", help_heading = "Rar")]
@@ -0,0 +1,88 @@
+use std::{ffi::OsStr, marker::PhantomData};
+
+use clap::{
+    builder::{StringValueParser, TypedValueParser},
+    Arg, Command, Error, PossibleValue,
+};
+
+use crate::ArgEnum;
+
+/// Lists the possible values of `E` for help and completions, without rejecting other values.
+///
+/// Used for enums which accept unknown values through `other` or prefixes through `infer`, the
+/// value is then checked when parsing it with [`ArgEnum::from_str`].
+struct ArgEnumValueParser<E> {
+    pseudo_values: Vec<PossibleValue<'static>>,
+    marker: PhantomData<fn() -> E>,
+}
+
+impl<E> Clone for ArgEnumValueParser<E> {
+    fn clone(&self) -> Self {
+        Self {
+            pseudo_values: self.pseudo_values.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E: ArgEnum + 'static> TypedValueParser for ArgEnumValueParser<E> {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue<'static>> + '_>> {
+        Some(Box::new(
+            E::value_variants()
+                .iter()
+                .filter_map(E::to_possible_value)
+                .chain(self.pseudo_values.iter().cloned()),
+        ))
+    }
+}
+
+/// Set the possible values of an `arg_enum` field, along with its `pseudo_values`.
+///
+/// # Panics
+///
+/// If a variant of `E` has the name or an alias of a pseudo value.
+#[doc(hidden)]
+pub fn arg_enum_values<'a, E: ArgEnum + 'static>(
+    arg: Arg<'a>,
+    pseudo_values: Vec<PossibleValue<'static>>,
+) -> Arg<'a> {
+    for pseudo_value in &pseudo_values {
+        if let Some(value) = E::value_variants()
+            .iter()
+            .filter_map(E::to_possible_value)
+            .find(|value| value.matches(pseudo_value.get_name(), false))
+        {
+            panic!(
+                "Argument '{}': variant `{}` collides with the pseudo value `{}`",
+                arg.get_id(),
+                value.get_name(),
+                pseudo_value.get_name()
+            );
+        }
+    }
+
+    if E::has_other() || E::infer() {
+        arg.value_parser(ArgEnumValueParser::<E> {
+            pseudo_values,
+            marker: PhantomData,
+        })
+    } else {
+        arg.possible_values(
+            E::value_variants()
+                .iter()
+                .filter_map(E::to_possible_value)
+                .chain(pseudo_values),
+        )
+    }
+}
@@ -5,8 +5,14 @@
 //!
 //! ## But why?
 //! Yeah I know, reinventing the wheel, etc. I needed a project.
+//!
+//! ## Features
+//! - `regex`: enables the `#[clap(regex = "...")]` field validation
 
+mod arg_enum;
 pub mod rename;
+mod secret;
+mod with_args;
 
 use std::ffi::OsString;
 
@@ -18,6 +24,15 @@ pub use clap_derive_darling_macro::{ArgEnum, Args, Parser, Subcommand};
 
 pub use once_cell::race::OnceBox;
 
+#[cfg(feature = "regex")]
+pub use regex;
+
+#[doc(hidden)]
+pub use arg_enum::arg_enum_values;
+
+pub use secret::Secret;
+pub use with_args::WithArgs;
+
 /// Parse command-line arguments into `Self`.
 ///
 /// The primary one-stop-shop trait used to create an instance of a `clap`
@@ -88,16 +103,14 @@ pub trait Clap: FromArgMatches + IntoApp + Sized {
     fn parse() -> Self {
         let matches = <Self as IntoApp>::into_app().get_matches();
         <Self as FromArgMatches>::from_arg_matches(&matches, Vec::new())
-            .expect("IntoApp validated everything")
+            .unwrap_or_else(|e| e.format(&mut <Self as IntoApp>::into_app()).exit())
     }
 
     /// Parse from `std::env::args_os()`, return Err on error.
     fn try_parse() -> Result<Self, Error> {
         let matches = <Self as IntoApp>::into_app().try_get_matches()?;
-        Ok(
-            <Self as FromArgMatches>::from_arg_matches(&matches, Vec::new())
-                .expect("IntoApp validated everything"),
-        )
+        <Self as FromArgMatches>::from_arg_matches(&matches, Vec::new())
+            .map_err(|e| e.format(&mut <Self as IntoApp>::into_app()))
     }
 
     /// Parse from iterator, exit on error
@@ -109,7 +122,7 @@ pub trait Clap: FromArgMatches + IntoApp + Sized {
     {
         let matches = <Self as IntoApp>::into_app().get_matches_from(itr);
         <Self as FromArgMatches>::from_arg_matches(&matches, Vec::new())
-            .expect("IntoApp validated everything")
+            .unwrap_or_else(|e| e.format(&mut <Self as IntoApp>::into_app()).exit())
     }
 
     /// Parse from iterator, return Err on error.
@@ -120,10 +133,8 @@ pub trait Clap: FromArgMatches + IntoApp + Sized {
         T: Into<OsString> + Clone,
     {
         let matches = <Self as IntoApp>::into_app().try_get_matches_from(itr)?;
-        Ok(
-            <Self as FromArgMatches>::from_arg_matches(&matches, Vec::new())
-                .expect("IntoApp validated everything"),
-        )
+        <Self as FromArgMatches>::from_arg_matches(&matches, Vec::new())
+            .map_err(|e| e.format(&mut <Self as IntoApp>::into_app()))
     }
 
     /// Update from iterator, exit on error
@@ -135,7 +146,11 @@ pub trait Clap: FromArgMatches + IntoApp + Sized {
     {
         // TODO find a way to get partial matches
         let matches = <Self as IntoApp>::into_app_for_update().get_matches_from(itr);
-        <Self as FromArgMatches>::update_from_arg_matches(self, &matches, Vec::new()).unwrap();
+        <Self as FromArgMatches>::update_from_arg_matches(self, &matches, Vec::new())
+            .unwrap_or_else(|e| {
+                e.format(&mut <Self as IntoApp>::into_app_for_update())
+                    .exit()
+            });
     }
 
     /// Update from iterator, return Err on error.
@@ -149,6 +164,11 @@ pub trait Clap: FromArgMatches + IntoApp + Sized {
         <Self as FromArgMatches>::update_from_arg_matches(self, &matches, Vec::new())?;
         Ok(())
     }
+
+    /// Names of the subcommands that were invoked, outermost first
+    fn subcommand_path(&self) -> Vec<&str> {
+        Vec::new()
+    }
 }
 
 /// Converts an instance of [`ArgMatches`] to a user-defined container.
@@ -204,7 +224,6 @@ pub trait FromArgMatches: Sized {
 ///
 /// # Example
 ///
-
 /// ```compile_fail
 /// #[derive(clap_derive_darling::Parser)]
 /// struct Args {
@@ -229,6 +248,12 @@ pub trait Args: FromArgMatches + Sized {
     ///
     /// See also [`IntoApp`].
     fn augment_args_for_update<'a>(app: App<'a>, prefix: Vec<&'static str>) -> App<'a>;
+    /// Names of the subcommands selected within `self`, outermost first
+    ///
+    /// See also [`Subcommand::subcommand_path`].
+    fn args_subcommand_path(&self) -> Vec<&str> {
+        Vec::new()
+    }
 }
 
 /// Parse a sub-command into a user-defined enum.
@@ -242,19 +267,26 @@ pub trait Args: FromArgMatches + Sized {
 ///
 /// # Example
 ///
-/// ```compile_fail
+/// ```
 /// #[derive(clap_derive_darling::Parser)]
 /// struct Args {
 ///     #[clap(subcommand)]
 ///     action: Action,
 /// }
 ///
-/// #[derive(clap::Subcommand)]
+/// #[derive(clap_derive_darling::Subcommand)]
 /// enum Action {
 ///     Add,
 ///     Remove,
 /// }
 /// ```
+///
+/// # Boxed subcommands
+///
+/// A `#[clap(subcommand)] field: Box<SubCmd>`, or `Option<Box<SubCmd>>`, allows recursive command
+/// trees. The apps of such a field are only built once its subcommand is given, so the app
+/// containing the field takes any subcommand in their place. As a consequence its help lists no
+/// subcommands, and neither do completions generated from the [`App`].
 pub trait Subcommand: FromArgMatches + Sized {
     /// Append to [`App`] so it can instantiate `Self`.
     ///
@@ -268,6 +300,145 @@ pub trait Subcommand: FromArgMatches + Sized {
     fn augment_subcommands_for_update<'a>(app: App<'a>, prefix: Vec<&'static str>) -> App<'a>;
     /// Test whether `Self` can parse a specific subcommand
     fn has_subcommand(name: &str) -> bool;
+    /// Names of the subcommands selected by `self`, outermost first
+    ///
+    /// e.g. `["remote", "add"]` for `app remote add origin`.
+    fn subcommand_path(&self) -> Vec<&str> {
+        Vec::new()
+    }
+    /// Test whether `Self` can be built when no subcommand was given
+    ///
+    /// This is true when a variant is marked `#[clap(default_subcommand)]`. That variant is then
+    /// built as if it was given without any args, so from their default values and env vars.
+    fn has_default_subcommand() -> bool {
+        false
+    }
+    /// Whether `Self` has an `#[clap(external_subcommand)]` variant
+    ///
+    /// Such an enum takes any subcommand name, so it can't be flattened into another enum.
+    const HAS_EXTERNAL_SUBCOMMAND: bool = false;
+}
+
+/// A [`Subcommand`] whose subcommands all share a set of [`Args`].
+///
+/// Derived alongside [`Subcommand`] for enums marked `#[clap(args = "CommonArgs")]`. Use
+/// [`WithArgs`] as the `#[clap(subcommand)]` field type to get at the parsed shared args.
+pub trait SubcommandArgs: Subcommand {
+    /// The args added to every subcommand.
+    type Args: Args;
+
+    /// Test whether the subcommand `name` was given the shared args.
+    ///
+    /// External subcommands are not, so [`WithArgs::args`] is `None` for them.
+    fn has_shared_args(name: &str) -> bool;
+    /// The prefix the shared args of the subcommand `name` were added with.
+    ///
+    /// This follows the `prefix` policy of the variant, starting from the `prefix` of the enum.
+    fn shared_args_prefix(name: &str, prefix: Vec<&'static str>) -> Vec<&'static str>;
+}
+
+/// Dispatch a parsed subcommand to its handler.
+///
+/// Derived alongside [`Subcommand`] when the enum is marked `#[clap(run)]`, optionally with
+/// `#[clap(run(context = "Type", output = "Type"))]` (both default to `()`). Every variant, skipped
+/// ones included, then names a handler with `#[clap(run = "path::to::handler")]`, which is called with the context
+/// followed by the variant's fields, or uses a bare `#[clap(run)]` to delegate to the nested
+/// subcommand it holds.
+///
+/// # Example
+///
+/// ```
+/// use clap_derive_darling::{Clap, Parser, Run, Subcommand};
+///
+/// #[derive(Parser)]
+/// struct Args {
+///     #[clap(subcommand)]
+///     action: Action,
+/// }
+///
+/// #[derive(Subcommand)]
+/// #[clap(run(context = "&str", output = "Result<String, String>"))]
+/// enum Action {
+///     #[clap(run = "add")]
+///     Add { name: String },
+///     #[clap(run = "list")]
+///     List,
+/// }
+///
+/// fn add(prefix: &str, name: String) -> Result<String, String> {
+///     Ok(format!("{}{}", prefix, name))
+/// }
+///
+/// fn list(_: &str) -> Result<String, String> {
+///     Err("nothing to list".to_string())
+/// }
+///
+/// let args = Args::parse_from(["app", "add", "thing"]);
+///
+/// assert_eq!(args.action.run("added "), Ok("added thing".to_string()));
+/// ```
+pub trait Run<C> {
+    /// What the handlers return.
+    type Output;
+    /// Call the handler for the parsed variant.
+    fn run(self, context: C) -> Self::Output;
+}
+
+/// Append a boxed `#[clap(subcommand)]` field.
+///
+/// Boxed subcommands make recursive command trees possible, so their apps are built lazily: the
+/// app takes any subcommand in their place, and [`boxed_subcommand_matches`] builds them once one
+/// is given.
+#[doc(hidden)]
+pub fn augment_boxed_subcommands(app: App<'_>) -> App<'_> {
+    app.allow_external_subcommands(true)
+        .allow_invalid_utf8_for_external_subcommands(true)
+}
+
+/// Parse the subcommand given for a boxed `#[clap(subcommand)]` field of type `T`, within the app
+/// `name`.
+///
+/// Only the apps of `T` are built, any boxed subcommands within them are left for the next call.
+#[doc(hidden)]
+pub fn boxed_subcommand_matches<T: Subcommand>(
+    name: &str,
+    matches: &ArgMatches,
+    prefix: Vec<&'static str>,
+) -> Result<ArgMatches, Error> {
+    let app = T::augment_subcommands(App::new(name).no_binary_name(true), prefix);
+
+    app.try_get_matches_from(boxed_subcommand_args(matches))
+}
+
+/// [`boxed_subcommand_matches`] for [`FromArgMatches::update_from_arg_matches`], which builds the
+/// apps of `T` with [`Subcommand::augment_subcommands_for_update`].
+#[doc(hidden)]
+pub fn boxed_subcommand_matches_for_update<T: Subcommand>(
+    name: &str,
+    matches: &ArgMatches,
+    prefix: Vec<&'static str>,
+) -> Result<ArgMatches, Error> {
+    let app = T::augment_subcommands_for_update(App::new(name).no_binary_name(true), prefix);
+
+    app.try_get_matches_from(boxed_subcommand_args(matches))
+}
+
+/// The external subcommand taken in place of a boxed subcommand, along with its args
+fn boxed_subcommand_args(matches: &ArgMatches) -> Vec<OsString> {
+    matches
+        .subcommand()
+        .map(|(name, sub_matches)| {
+            std::iter::once(OsString::from(name))
+                .chain(
+                    sub_matches
+                        .values_of_os("")
+                        .into_iter()
+                        .flatten()
+                        .map(OsString::from),
+                )
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// Parse arguments into enums.
@@ -298,20 +469,89 @@ pub trait ArgEnum: Sized + Clone {
     fn value_variants<'a>() -> &'a [Self];
 
     /// Parse an argument into `Self`.
+    ///
+    /// When [`ArgEnum::infer`] is set, an unambiguous prefix of a value also matches it.
     fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
-        Self::value_variants()
+        let possible_value = |v: &Self| {
+            v.to_possible_value()
+                .expect("ArgEnum::value_variants contains only values with a corresponding ArgEnum::to_possible_value")
+        };
+
+        if let Some(v) = Self::value_variants()
             .iter()
-            .find(|v| {
-                v.to_possible_value()
-                    .expect("ArgEnum::value_variants contains only values with a corresponding ArgEnum::to_possible_value")
-                    .matches(input, ignore_case)
-            })
-            .cloned()
-            .ok_or_else(|| format!("Invalid variant: {}", input))
+            .find(|v| possible_value(v).matches(input, ignore_case))
+        {
+            return Ok(v.clone());
+        }
+
+        if Self::infer() && !input.is_empty() {
+            let is_prefix = |name: &str| {
+                name.get(..input.len()).is_some_and(|start| {
+                    if ignore_case {
+                        start.eq_ignore_ascii_case(input)
+                    } else {
+                        start == input
+                    }
+                })
+            };
+
+            let candidates = Self::value_variants()
+                .iter()
+                .filter(|v| possible_value(v).get_name_and_aliases().any(is_prefix))
+                .collect::<Vec<_>>();
+
+            match candidates.as_slice() {
+                [] => {}
+                [v] => return Ok((*v).clone()),
+                candidates => {
+                    let names = candidates
+                        .iter()
+                        .map(|v| possible_value(v).get_name().to_string())
+                        .collect::<Vec<_>>();
+                    return Err(format!(
+                        "Ambiguous variant: {}, could be one of: {}",
+                        input,
+                        names.join(", ")
+                    ));
+                }
+            }
+        }
+
+        Self::from_other(input).ok_or_else(|| format!("Invalid variant: {}", input))
     }
 
     /// The canonical argument value.
     ///
-    /// The value is `None` for skipped variants.
+    /// The value is `None` for skipped variants and the `other` variant.
     fn to_possible_value<'a>(&self) -> Option<PossibleValue<'a>>;
+
+    /// Whether unknown values are parsed into a catch-all `other` variant.
+    ///
+    /// `arg_enum` fields still list the possible values but accept any value in that case.
+    fn has_other() -> bool {
+        false
+    }
+
+    /// Parse an unknown value into the catch-all `other` variant.
+    fn from_other(_input: &str) -> Option<Self> {
+        None
+    }
+
+    /// Whether unambiguous prefixes of values are accepted.
+    ///
+    /// `arg_enum` fields still list the possible values but accept any value in that case.
+    fn infer() -> bool {
+        false
+    }
+
+    /// Whether `arg_enum` fields match values case-insensitively by default.
+    fn ignore_case() -> bool {
+        false
+    }
+
+    /// The doc comment of the enum, used as the long help of `arg_enum`
+    /// fields which don't have one of their own.
+    fn long_help<'a>() -> Option<&'a str> {
+        None
+    }
 }
@@ -0,0 +1,72 @@
+use std::{
+    fmt::{self, Debug, Display},
+    str::FromStr,
+};
+
+/// A value that must not be leaked through `Debug` or `Display` output.
+///
+/// Fields of type `Secret<T>` (optionally wrapped in `Option` or `Vec`) are
+/// treated as if they were marked `#[clap(secret)]`, hiding their env and
+/// default values from help output.
+///
+/// # Example
+///
+/// ```
+/// use clap_derive_darling::{Clap, Parser, Secret};
+///
+/// #[derive(Parser, Debug)]
+/// struct Args {
+///     #[clap(long)]
+///     token: Secret<String>,
+/// }
+///
+/// let args = Args::parse_from(["app", "--token", "hunter2"]);
+///
+/// assert_eq!(args.token.expose_secret(), "hunter2");
+/// assert!(!format!("{:?}", args).contains("hunter2"));
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    /// Wrap `value`.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the wrapped value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwrap the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: FromStr> FromStr for Secret<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        T::from_str(s).map(Self)
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+impl<T> Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
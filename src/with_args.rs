@@ -0,0 +1,133 @@
+use std::ops::{Deref, DerefMut};
+
+use clap::{App, ArgMatches, Error};
+
+use crate::{FromArgMatches, Subcommand, SubcommandArgs};
+
+/// A subcommand along with the args shared by all of its subcommands.
+///
+/// # Example
+///
+/// ```
+/// use clap_derive_darling::{Args, Clap, Parser, Subcommand, WithArgs};
+///
+/// #[derive(Parser)]
+/// struct Application {
+///     #[clap(subcommand)]
+///     command: WithArgs<Command>,
+/// }
+///
+/// #[derive(Subcommand, Debug, PartialEq)]
+/// #[clap(args = "CommonArgs")]
+/// enum Command {
+///     Build,
+///     Test,
+/// }
+///
+/// #[derive(Args)]
+/// struct CommonArgs {
+///     #[clap(long)]
+///     verbose: bool,
+/// }
+///
+/// let app = Application::parse_from(["app", "test", "--verbose"]);
+///
+/// assert_eq!(app.command.subcommand, Command::Test);
+/// assert!(app.command.args.unwrap().verbose);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithArgs<S: SubcommandArgs> {
+    /// The shared args, as given to the invoked subcommand.
+    ///
+    /// `None` when the invoked subcommand doesn't take them: an external subcommand, or the
+    /// default subcommand when no subcommand was given.
+    pub args: Option<S::Args>,
+    /// The invoked subcommand.
+    pub subcommand: S,
+}
+
+impl<S: SubcommandArgs> FromArgMatches for WithArgs<S> {
+    fn from_arg_matches(matches: &ArgMatches, prefix: Vec<&'static str>) -> Result<Self, Error> {
+        let subcommand = <S as FromArgMatches>::from_arg_matches(matches, prefix.clone())?;
+
+        let args = match matches.subcommand() {
+            Some((name, sub_matches)) if S::has_shared_args(name) => {
+                Some(<S::Args as FromArgMatches>::from_arg_matches(
+                    sub_matches,
+                    S::shared_args_prefix(name, prefix),
+                )?)
+            }
+            _ => None,
+        };
+
+        Ok(Self { args, subcommand })
+    }
+
+    fn update_from_arg_matches(
+        &mut self,
+        matches: &ArgMatches,
+        prefix: Vec<&'static str>,
+    ) -> Result<(), Error> {
+        if let Some((name, sub_matches)) = matches.subcommand() {
+            if S::has_shared_args(name) {
+                let prefix = S::shared_args_prefix(name, prefix.clone());
+
+                match &mut self.args {
+                    Some(args) => <S::Args as FromArgMatches>::update_from_arg_matches(
+                        args,
+                        sub_matches,
+                        prefix,
+                    )?,
+                    None => {
+                        self.args = Some(<S::Args as FromArgMatches>::from_arg_matches(
+                            sub_matches,
+                            prefix,
+                        )?)
+                    }
+                }
+            } else {
+                self.args = None;
+            }
+        }
+
+        <S as FromArgMatches>::update_from_arg_matches(&mut self.subcommand, matches, prefix)
+    }
+}
+
+impl<S: SubcommandArgs> Subcommand for WithArgs<S> {
+    const HAS_EXTERNAL_SUBCOMMAND: bool = S::HAS_EXTERNAL_SUBCOMMAND;
+
+    fn augment_subcommands<'a>(app: App<'a>, prefix: Vec<&'static str>) -> App<'a> {
+        S::augment_subcommands(app, prefix)
+    }
+
+    fn augment_subcommands_for_update<'a>(app: App<'a>, prefix: Vec<&'static str>) -> App<'a> {
+        S::augment_subcommands_for_update(app, prefix)
+    }
+
+    fn has_subcommand(name: &str) -> bool {
+        S::has_subcommand(name)
+    }
+
+    fn has_default_subcommand() -> bool {
+        S::has_default_subcommand()
+    }
+
+    fn subcommand_path(&self) -> Vec<&str> {
+        self.subcommand.subcommand_path()
+    }
+}
+
+impl<S: SubcommandArgs> Deref for WithArgs<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.subcommand
+    }
+}
+
+impl<S: SubcommandArgs> DerefMut for WithArgs<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.subcommand
+    }
+}
@@ -42,6 +42,7 @@ struct Application {
     author = "Test Author",
     help_heading = "Other heading"
 )]
+#[allow(dead_code)]
 struct Flatten {
     #[clap(skip)]
     other: u64,
@@ -50,6 +51,7 @@ struct Flatten {
 }
 
 #[derive(Subcommand)]
+#[allow(dead_code)]
 enum Command {
     First(FirstCommand),
     #[clap(name = "2nd", version = "9.9.9", help_heading = "Rar")]
@@ -1,4 +1,3 @@
-use clap::App;
 use clap_derive_darling::{ArgEnum, Args, Clap, Parser, Subcommand};
 
 #[derive(Parser)]
@@ -125,6 +124,7 @@ fn test_subcommand() {
     }
 
     #[derive(Subcommand)]
+    #[allow(dead_code)]
     enum Command {
         /// First about
         ///
@@ -178,17 +178,73 @@ fn test_subcommand() {
 
     assert!(Application::try_parse_from(args).is_err());
 
-    // External commands are broken
+    let args = vec!["app_name", "--name", "rar", "external", "--external-arg"];
 
-    // let args = vec!["app_name", "--name", "external", "--external-arg"];
+    let flags = Application::try_parse_from(args).unwrap();
+
+    assert!(matches!(flags.command, Command::External(..)));
+
+    if let Command::External(external) = flags.command {
+        assert_eq!(external, vec!["external", "--external-arg"]);
+    }
+
+    let args = vec!["app_name", "--name", "rar", "first", "--arg", "thing"];
+
+    let mut flags = Application::try_parse_from(args).unwrap();
+
+    flags
+        .try_update_from(vec!["app_name", "--name", "rar", "other", "value"])
+        .unwrap();
+
+    if let Command::External(external) = &flags.command {
+        assert_eq!(external, &vec!["other", "value"]);
+    } else {
+        panic!("Expected external subcommand");
+    }
+
+    flags
+        .try_update_from(vec!["app_name", "--name", "rar", "first", "--arg", "again"])
+        .unwrap();
+
+    assert!(matches!(flags.command, Command::First(..)));
+
+    assert!(<Command as clap_derive_darling::Subcommand>::has_subcommand("anything"));
+}
+
+#[test]
+fn test_external_subcommand_os_string() {
+    use std::ffi::OsString;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug)]
+    enum Command {
+        Known,
+        #[clap(external_subcommand)]
+        External(Vec<OsString>),
+    }
 
-    // let flags = Application::try_parse_from(args).unwrap();
+    let flags = Application::try_parse_from(vec!["app_name", "known"]).unwrap();
+    assert!(matches!(flags.command, Command::Known));
 
-    // assert!(matches!(flags.command, Command::External(..)));
+    let flags = Application::try_parse_from(vec!["app_name", "plugin", "--flag", "value"]).unwrap();
 
-    // if let Command::External(external) = flags.command {
-    //     assert_eq!(external, vec!["--external-arg"]);
-    // }
+    if let Command::External(external) = flags.command {
+        assert_eq!(
+            external,
+            vec![
+                OsString::from("plugin"),
+                OsString::from("--flag"),
+                OsString::from("value")
+            ]
+        );
+    } else {
+        panic!("Expected external subcommand");
+    }
 }
 
 #[test]
@@ -222,11 +278,1770 @@ fn test_arg_enum() {
 
     let args = vec!["app_name", "--first", "variant-0", "--second", "VARIANT_1"];
 
-    // FIXME: --third should be able to be added without a value...
-
     let flags = Application::try_parse_from(args).unwrap();
 
     assert!(matches!(flags.first, First::Variant0));
     assert!(matches!(flags.second, Some(Second::Variant1)));
-    assert!(matches!(flags.third, None));
+    assert!(flags.third.is_none());
+
+    let args = vec!["app_name", "--first", "variant-1", "--third"];
+
+    let flags = Application::try_parse_from(args).unwrap();
+
+    assert!(matches!(flags.third, Some(None)));
+
+    let args = vec!["app_name", "--first", "variant-1", "--third", "VARIANT_0"];
+
+    let flags = Application::try_parse_from(args).unwrap();
+
+    assert!(matches!(flags.third, Some(Some(Second::Variant0))));
+}
+
+#[test]
+fn test_option_option() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long)]
+        jobs: Option<Option<u32>>,
+        #[clap(long, range = "1..=9")]
+        level: Option<Option<u8>>,
+        #[clap(long, parse(from_str))]
+        name: Option<Option<String>>,
+        #[clap(long, possible_values("auto", "never"))]
+        color: Option<Option<String>>,
+        #[clap(long, arg_enum)]
+        mode: Option<Option<Mode>>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+    assert_eq!(flags.jobs, None);
+    assert_eq!(flags.level, None);
+    assert_eq!(flags.name, None);
+    assert_eq!(flags.color, None);
+    assert_eq!(flags.mode, None);
+
+    let flags = Application::try_parse_from(vec![
+        "app_name", "--jobs", "--level", "--name", "--color", "--mode",
+    ])
+    .unwrap();
+    assert_eq!(flags.jobs, Some(None));
+    assert_eq!(flags.level, Some(None));
+    assert_eq!(flags.name, Some(None));
+    assert_eq!(flags.color, Some(None));
+    assert_eq!(flags.mode, Some(None));
+
+    let mut flags = Application::try_parse_from(vec![
+        "app_name", "--jobs", "4", "--level", "3", "--name", "x", "--color", "auto", "--mode",
+        "slow",
+    ])
+    .unwrap();
+    assert_eq!(flags.jobs, Some(Some(4)));
+    assert_eq!(flags.level, Some(Some(3)));
+    assert_eq!(flags.name, Some(Some("x".to_string())));
+    assert_eq!(flags.color, Some(Some("auto".to_string())));
+    assert_eq!(flags.mode, Some(Some(Mode::Slow)));
+
+    assert!(Application::try_parse_from(vec!["app_name", "--level", "0"]).is_err());
+
+    flags
+        .try_update_from(vec!["app_name", "--jobs", "--mode"])
+        .unwrap();
+    assert_eq!(flags.jobs, Some(None));
+    assert_eq!(flags.mode, Some(None));
+}
+
+#[test]
+fn test_default_missing_value() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(
+            long,
+            arg_enum,
+            default_value = "auto",
+            default_missing_value = "always"
+        )]
+        color: Color,
+        #[clap(long, default_missing_value = "8")]
+        jobs: Option<u32>,
+        #[clap(long, arg_enum, default_missing_value = "always")]
+        pager: Option<Option<Color>>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    enum Color {
+        Auto,
+        Always,
+        Never,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+    assert_eq!(flags.color, Color::Auto);
+    assert_eq!(flags.jobs, None);
+    assert_eq!(flags.pager, None);
+
+    let flags =
+        Application::try_parse_from(vec!["app_name", "--color", "--jobs", "--pager"]).unwrap();
+    assert_eq!(flags.color, Color::Always);
+    assert_eq!(flags.jobs, Some(8));
+    assert_eq!(flags.pager, Some(Some(Color::Always)));
+
+    let flags = Application::try_parse_from(vec![
+        "app_name", "--color", "never", "--jobs", "2", "--pager", "never",
+    ])
+    .unwrap();
+    assert_eq!(flags.color, Color::Never);
+    assert_eq!(flags.jobs, Some(2));
+    assert_eq!(flags.pager, Some(Some(Color::Never)));
+}
+
+#[test]
+fn test_option_vec() {
+    use std::collections::BTreeSet;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long)]
+        tags: Option<Vec<String>>,
+        #[clap(long, arg_enum, value_delimiter = ',')]
+        modes: Option<BTreeSet<Mode>>,
+        file: Option<String>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+    assert_eq!(flags.tags, None);
+    assert_eq!(flags.modes, None);
+
+    let flags = Application::try_parse_from(vec!["app_name", "--tags", "--modes"]).unwrap();
+    assert_eq!(flags.tags, Some(vec![]));
+    assert_eq!(flags.modes, Some(BTreeSet::new()));
+
+    let mut flags = Application::try_parse_from(vec![
+        "app_name",
+        "--tags",
+        "a",
+        "--tags",
+        "b",
+        "--tags",
+        "c",
+        "--modes",
+        "slow,fast",
+    ])
+    .unwrap();
+    assert_eq!(
+        flags.tags,
+        Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+    );
+    assert_eq!(flags.modes, Some(BTreeSet::from([Mode::Fast, Mode::Slow])));
+
+    flags.try_update_from(vec!["app_name", "--tags"]).unwrap();
+    assert_eq!(flags.tags, Some(vec![]));
+    assert_eq!(flags.modes, None);
+
+    flags
+        .try_update_from(vec!["app_name", "--modes", "fast"])
+        .unwrap();
+    assert_eq!(flags.tags, None);
+    assert_eq!(flags.modes, Some(BTreeSet::from([Mode::Fast])));
+
+    // Each occurrence takes at most one value, the rest goes to the positional
+    let flags = Application::try_parse_from(vec!["app_name", "--tags", "x", "file.txt"]).unwrap();
+    assert_eq!(flags.tags, Some(vec!["x".to_string()]));
+    assert_eq!(flags.file.as_deref(), Some("file.txt"));
+
+    let flags = Application::try_parse_from(vec!["app_name", "--tags", "--", "file.txt"]).unwrap();
+    assert_eq!(flags.tags, Some(vec![]));
+    assert_eq!(flags.file.as_deref(), Some("file.txt"));
+}
+
+#[test]
+fn test_secret() {
+    use clap::IntoApp;
+    use clap_derive_darling::Secret;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, env = "TEST_SECRET_TOKEN")]
+        token: Secret<String>,
+
+        #[clap(long, env = "TEST_SECRET_PASSWORD", secret)]
+        password: Option<String>,
+    }
+
+    std::env::set_var("TEST_SECRET_TOKEN", "token-from-env");
+    std::env::set_var("TEST_SECRET_PASSWORD", "password-from-env");
+
+    let flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+
+    assert_eq!(flags.token.expose_secret(), "token-from-env");
+    assert_eq!(flags.password.as_deref(), Some("password-from-env"));
+
+    let debug = format!("{:?}", flags.token);
+    assert!(!debug.contains("token-from-env"));
+    assert_eq!(format!("{}", flags.token), "<redacted>");
+
+    let mut help = Vec::new();
+    Application::into_app().write_long_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("TEST_SECRET_TOKEN"));
+    assert!(!help.contains("token-from-env"));
+    assert!(!help.contains("password-from-env"));
+}
+
+#[test]
+fn test_validation() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, range = "1..=65535", default_value = "8080")]
+        port: u16,
+
+        #[clap(long, min_len = 2, max_len = 4)]
+        code: Option<String>,
+
+        #[clap(long, non_empty)]
+        label: Option<String>,
+
+        #[clap(long, min_occurrences = 1, max_occurrences = 2)]
+        tag: Vec<String>,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "--tag", "a"]).unwrap();
+
+    assert_eq!(flags.port, 8080);
+    assert_eq!(flags.tag, vec!["a"]);
+
+    let flags = Application::try_parse_from(vec![
+        "app_name", "--port", "443", "--code", "abc", "--tag", "a", "--tag", "b",
+    ])
+    .unwrap();
+
+    assert_eq!(flags.port, 443);
+    assert_eq!(flags.code.as_deref(), Some("abc"));
+    assert_eq!(flags.tag, vec!["a", "b"]);
+
+    let err = Application::try_parse_from(vec!["app_name", "--port", "0", "--tag", "a"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--port"));
+    assert!(err.contains("value must be in the range 1..=65535"));
+
+    let err = Application::try_parse_from(vec!["app_name", "--code", "a", "--tag", "a"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--code"));
+    assert!(err.contains("at least 2 characters"));
+
+    let err = Application::try_parse_from(vec!["app_name", "--code", "abcde", "--tag", "a"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("at most 4 characters"));
+
+    let err = Application::try_parse_from(vec!["app_name", "--label", "", "--tag", "a"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--label"));
+
+    assert!(Application::try_parse_from(vec!["app_name"]).is_err());
+    assert!(Application::try_parse_from(vec![
+        "app_name", "--tag", "a", "--tag", "b", "--tag", "c"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_validation_regex() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, regex = "^[a-z]+$")]
+        name: Option<String>,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "--name", "abc"]).unwrap();
+    assert_eq!(flags.name.as_deref(), Some("abc"));
+
+    for _ in 0..2 {
+        let err = Application::try_parse_from(vec!["app_name", "--name", "ABC"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--name"));
+        assert!(err.contains("value must match the pattern ^[a-z]+$"));
+    }
+}
+
+#[test]
+fn test_min_occurrences() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, min_occurrences = 2)]
+        tag: Vec<String>,
+    }
+
+    let err = Application::try_parse_from(vec!["app_name", "--tag", "a"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--tag must be given at least 2 times"));
+
+    let flags = Application::try_parse_from(vec!["app_name", "--tag", "a", "--tag", "b"]).unwrap();
+    assert_eq!(flags.tag, vec!["a", "b"]);
+}
+
+#[test]
+fn test_possible_values() {
+    use clap::IntoApp;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, possible_values("fast", "slow"))]
+        speed: String,
+
+        #[clap(long, possible_values("red", "green"), ignore_case)]
+        colour: Option<String>,
+    }
+
+    let flags =
+        Application::try_parse_from(vec!["app_name", "--speed", "fast", "--colour", "GREEN"])
+            .unwrap();
+
+    assert_eq!(flags.speed, "fast");
+    assert_eq!(flags.colour.as_deref(), Some("green"));
+
+    let err = Application::try_parse_from(vec!["app_name", "--speed", "medium"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("fast"));
+    assert!(err.contains("slow"));
+
+    assert!(Application::try_parse_from(vec!["app_name", "--speed", "FAST"]).is_err());
+
+    let mut help = Vec::new();
+    Application::into_app().write_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("[possible values: fast, slow]"));
+}
+
+#[test]
+fn test_unit_subcommand() {
+    use clap::IntoApp;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        action: Action,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Action {
+        /// Add a thing
+        ///
+        /// Adds a thing, at length
+        Add,
+        /// Remove a thing
+        Remove,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "add"]).unwrap();
+    assert_eq!(flags.action, Action::Add);
+
+    let flags = Application::try_parse_from(vec!["app_name", "remove"]).unwrap();
+    assert_eq!(flags.action, Action::Remove);
+
+    assert!(Application::try_parse_from(vec!["app_name", "remove", "--what"]).is_err());
+
+    let mut flags = Application::try_parse_from(vec!["app_name", "add"]).unwrap();
+    flags.try_update_from(vec!["app_name", "remove"]).unwrap();
+    assert_eq!(flags.action, Action::Remove);
+    flags.try_update_from(vec!["app_name", "remove"]).unwrap();
+    assert_eq!(flags.action, Action::Remove);
+
+    let mut app = Application::into_app();
+    let add = app.find_subcommand_mut("add").unwrap();
+    assert_eq!(add.get_about(), Some("Add a thing"));
+    assert_eq!(add.get_long_about(), Some("Adds a thing, at length"));
+}
+
+#[test]
+fn test_tuple_subcommand() {
+    use std::path::PathBuf;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        action: Action,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Action {
+        Copy(PathBuf, PathBuf),
+        Rename(#[clap(name = "from")] String, String, #[clap(long)] bool),
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "copy", "a.txt", "b.txt"]).unwrap();
+    assert_eq!(
+        flags.action,
+        Action::Copy(PathBuf::from("a.txt"), PathBuf::from("b.txt"))
+    );
+
+    let flags =
+        Application::try_parse_from(vec!["app_name", "rename", "old", "new", "--bool"]).unwrap();
+    assert_eq!(
+        flags.action,
+        Action::Rename("old".to_string(), "new".to_string(), true)
+    );
+
+    let mut flags = Application::try_parse_from(vec!["app_name", "copy", "a", "b"]).unwrap();
+    flags
+        .try_update_from(vec!["app_name", "copy", "c", "d"])
+        .unwrap();
+    assert_eq!(
+        flags.action,
+        Action::Copy(PathBuf::from("c"), PathBuf::from("d"))
+    );
+
+    let err = Application::try_parse_from(vec!["app_name", "copy", "a"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("<PATH_BUF_2>"));
+}
+
+#[test]
+fn test_flatten_subcommand() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        Status,
+        #[clap(flatten)]
+        Remote(RemoteCommand),
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum RemoteCommand {
+        Push {
+            #[clap(long)]
+            force: bool,
+        },
+        Pull,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "status"]).unwrap();
+    assert_eq!(flags.command, Command::Status);
+
+    let mut flags = Application::try_parse_from(vec!["app_name", "push"]).unwrap();
+    assert_eq!(
+        flags.command,
+        Command::Remote(RemoteCommand::Push { force: false })
+    );
+
+    flags
+        .try_update_from(vec!["app_name", "push", "--force"])
+        .unwrap();
+    assert_eq!(
+        flags.command,
+        Command::Remote(RemoteCommand::Push { force: true })
+    );
+
+    flags.try_update_from(vec!["app_name", "pull"]).unwrap();
+    assert_eq!(flags.command, Command::Remote(RemoteCommand::Pull));
+
+    flags.try_update_from(vec!["app_name", "status"]).unwrap();
+    assert_eq!(flags.command, Command::Status);
+
+    assert!(<Command as clap_derive_darling::Subcommand>::has_subcommand("pull"));
+    assert!(!<Command as clap_derive_darling::Subcommand>::has_subcommand("fetch"));
+}
+
+#[test]
+fn test_flatten_subcommand_external() {
+    use clap_derive_darling::Subcommand as _;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        #[clap(external_subcommand)]
+        Other(Vec<String>),
+        #[clap(flatten)]
+        Remote(RemoteCommand),
+        Status,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum RemoteCommand {
+        Pull,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "status"]).unwrap();
+    assert_eq!(flags.command, Command::Status);
+
+    let flags = Application::try_parse_from(vec!["app_name", "pull"]).unwrap();
+    assert_eq!(flags.command, Command::Remote(RemoteCommand::Pull));
+
+    let flags = Application::try_parse_from(vec!["app_name", "fetch", "origin"]).unwrap();
+    assert_eq!(
+        flags.command,
+        Command::Other(vec!["fetch".to_string(), "origin".to_string()])
+    );
+
+    const { assert!(Command::HAS_EXTERNAL_SUBCOMMAND) };
+    const { assert!(!RemoteCommand::HAS_EXTERNAL_SUBCOMMAND) };
+}
+
+#[test]
+fn test_subcommand_aliases() {
+    use clap::IntoApp;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        #[clap(alias = "rm", visible_aliases("del", "delete"), display_order = 2)]
+        Remove,
+        #[clap(short_flag = "a", long_flag = "add", display_order = 1)]
+        Add,
+        #[clap(hide)]
+        Debug,
+    }
+
+    for name in ["remove", "rm", "del", "delete"] {
+        let flags = Application::try_parse_from(vec!["app_name", name]).unwrap();
+        assert_eq!(flags.command, Command::Remove);
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "-a"]).unwrap();
+    assert_eq!(flags.command, Command::Add);
+
+    let flags = Application::try_parse_from(vec!["app_name", "--add"]).unwrap();
+    assert_eq!(flags.command, Command::Add);
+
+    let flags = Application::try_parse_from(vec!["app_name", "debug"]).unwrap();
+    assert_eq!(flags.command, Command::Debug);
+
+    assert!(<Command as clap_derive_darling::Subcommand>::has_subcommand("rm"));
+    assert!(<Command as clap_derive_darling::Subcommand>::has_subcommand("delete"));
+
+    let mut help = Vec::new();
+    Application::into_app().write_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(!help.contains("debug"));
+    assert!(help.contains("[aliases: del, delete]"));
+    assert!(help.find("add").unwrap() < help.find("remove").unwrap());
+}
+
+#[test]
+fn test_optional_subcommand() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long)]
+        verbose: bool,
+        #[clap(subcommand)]
+        command: Option<Command>,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        Build {
+            #[clap(long)]
+            release: bool,
+        },
+        Clean,
+    }
+
+    let mut flags = Application::try_parse_from(vec!["app_name", "--verbose"]).unwrap();
+    assert!(flags.verbose);
+    assert_eq!(flags.command, None);
+
+    flags.try_update_from(vec!["app_name", "build"]).unwrap();
+    assert_eq!(flags.command, Some(Command::Build { release: false }));
+
+    flags
+        .try_update_from(vec!["app_name", "build", "--release"])
+        .unwrap();
+    assert_eq!(flags.command, Some(Command::Build { release: true }));
+
+    flags.try_update_from(vec!["app_name"]).unwrap();
+    assert_eq!(flags.command, Some(Command::Build { release: true }));
+
+    let flags = Application::try_parse_from(vec!["app_name", "clean"]).unwrap();
+    assert_eq!(flags.command, Some(Command::Clean));
+}
+
+#[test]
+fn test_default_subcommand() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long)]
+        verbose: bool,
+        #[clap(long)]
+        port: Option<u16>,
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        #[clap(default_subcommand)]
+        Serve {
+            #[clap(long, default_value = "8080")]
+            port: u16,
+        },
+        Stop,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+    assert_eq!(flags.command, Command::Serve { port: 8080 });
+    assert_eq!(flags.port, None);
+
+    let flags = Application::try_parse_from(vec!["app_name", "serve", "--port", "80"]).unwrap();
+    assert_eq!(flags.command, Command::Serve { port: 80 });
+    assert_eq!(flags.port, None);
+
+    let mut flags = Application::try_parse_from(vec!["app_name", "--port", "81"]).unwrap();
+    assert_eq!(flags.command, Command::Serve { port: 8080 });
+    assert_eq!(flags.port, Some(81));
+
+    flags
+        .try_update_from(vec!["app_name", "--port", "82", "serve", "--port", "83"])
+        .unwrap();
+    assert_eq!(flags.command, Command::Serve { port: 83 });
+    assert_eq!(flags.port, Some(82));
+
+    flags
+        .try_update_from(vec!["app_name", "--verbose"])
+        .unwrap();
+    assert_eq!(flags.command, Command::Serve { port: 83 });
+    assert!(flags.verbose);
+
+    let flags = Application::try_parse_from(vec!["app_name", "--verbose", "stop"]).unwrap();
+    assert_eq!(flags.command, Command::Stop);
+    assert!(flags.verbose);
+
+    let err = Application::try_parse_from(vec!["app_name", "serve", "--port", "x"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Invalid value \"x\" for '--port <PORT>'"));
+
+    assert!(<Command as clap_derive_darling::Subcommand>::has_default_subcommand());
+
+    #[derive(Parser, Debug)]
+    struct OptionalApplication {
+        #[clap(subcommand)]
+        command: Option<Command>,
+    }
+
+    let flags = OptionalApplication::try_parse_from(vec!["app_name"]).unwrap();
+    assert_eq!(flags.command, Some(Command::Serve { port: 8080 }));
+
+    #[derive(Parser, Debug)]
+    struct RequiredApplication {
+        #[clap(subcommand)]
+        command: RequiredCommand,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum RequiredCommand {
+        #[clap(default_subcommand)]
+        Connect {
+            #[clap(long)]
+            host: String,
+        },
+    }
+
+    let err = RequiredApplication::try_parse_from(vec!["app_name"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--host"));
+}
+
+#[test]
+fn test_subcommand_prefix() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(flatten = "db")]
+        db: Context,
+    }
+
+    #[derive(Args, Debug)]
+    #[clap(flatten("db"))]
+    struct Context {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        Connect(Settings),
+        #[clap(prefix = "reset")]
+        Reset(Settings),
+        #[clap(prefix = "inherit")]
+        Query {
+            #[clap(flatten = "query")]
+            settings: Settings,
+        },
+    }
+
+    #[derive(Args, Debug, PartialEq)]
+    #[clap(flatten("db"), flatten("db", "query"))]
+    struct Settings {
+        #[clap(long)]
+        uri: Option<String>,
+    }
+
+    let mut flags =
+        Application::try_parse_from(vec!["app_name", "connect", "--db-uri", "a"]).unwrap();
+    assert_eq!(
+        flags.db.command,
+        Command::Connect(Settings {
+            uri: Some("a".to_string())
+        })
+    );
+
+    flags
+        .try_update_from(vec!["app_name", "connect", "--db-uri", "b"])
+        .unwrap();
+    assert_eq!(
+        flags.db.command,
+        Command::Connect(Settings {
+            uri: Some("b".to_string())
+        })
+    );
+
+    let flags = Application::try_parse_from(vec!["app_name", "reset", "--uri", "c"]).unwrap();
+    assert_eq!(
+        flags.db.command,
+        Command::Reset(Settings {
+            uri: Some("c".to_string())
+        })
+    );
+
+    let flags =
+        Application::try_parse_from(vec!["app_name", "query", "--db-query-uri", "d"]).unwrap();
+    assert_eq!(
+        flags.db.command,
+        Command::Query {
+            settings: Settings {
+                uri: Some("d".to_string())
+            }
+        }
+    );
+}
+
+#[test]
+fn test_subcommand_run() {
+    use clap_derive_darling::Run;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug)]
+    #[clap(run(context = "&mut Vec<String>", output = "Result<(), String>"))]
+    enum Command {
+        #[clap(run = "status")]
+        Status,
+        #[clap(run = "add")]
+        Add { name: String },
+        #[clap(run = "copy")]
+        Copy(String, String),
+        #[clap(flatten)]
+        Remote(RemoteCommand),
+        #[clap(run)]
+        Nested {
+            #[clap(long)]
+            verbose: bool,
+            #[clap(subcommand)]
+            command: RemoteCommand,
+        },
+        #[clap(skip, run = "internal")]
+        Internal(u8),
+    }
+
+    #[derive(Subcommand, Debug)]
+    #[clap(run(context = "&mut Vec<String>", output = "Result<(), String>"))]
+    enum RemoteCommand {
+        #[clap(run = "remote_list")]
+        List,
+    }
+
+    fn status(log: &mut Vec<String>) -> Result<(), String> {
+        log.push("status".to_string());
+        Ok(())
+    }
+
+    fn add(_: &mut Vec<String>, name: String) -> Result<(), String> {
+        Err(format!("cannot add {}", name))
+    }
+
+    fn copy(log: &mut Vec<String>, from: String, to: String) -> Result<(), String> {
+        log.push(format!("copy {} {}", from, to));
+        Ok(())
+    }
+
+    fn remote_list(log: &mut Vec<String>) -> Result<(), String> {
+        log.push("remote list".to_string());
+        Ok(())
+    }
+
+    fn internal(log: &mut Vec<String>, code: u8) -> Result<(), String> {
+        log.push(format!("internal {}", code));
+        Ok(())
+    }
+
+    let mut log = Vec::new();
+
+    for args in [
+        vec!["app_name", "status"],
+        vec!["app_name", "copy", "a", "b"],
+        vec!["app_name", "list"],
+        vec!["app_name", "nested", "--verbose", "list"],
+    ] {
+        let flags = Application::try_parse_from(args).unwrap();
+        flags.command.run(&mut log).unwrap();
+    }
+
+    assert_eq!(
+        log,
+        vec!["status", "copy a b", "remote list", "remote list"]
+    );
+
+    let flags = Application::try_parse_from(vec!["app_name", "add", "thing"]).unwrap();
+    assert_eq!(
+        flags.command.run(&mut log),
+        Err("cannot add thing".to_string())
+    );
+
+    Command::Internal(3).run(&mut log).unwrap();
+    assert_eq!(log.last().map(String::as_str), Some("internal 3"));
+    assert!(Application::try_parse_from(vec!["app_name", "internal"]).is_err());
+}
+
+#[test]
+fn test_subcommand_path() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(flatten)]
+        global: Global,
+        #[clap(subcommand)]
+        command: Option<Command>,
+    }
+
+    #[derive(Args, Debug)]
+    struct Global {
+        #[clap(long)]
+        verbose: bool,
+    }
+
+    #[derive(Subcommand, Debug)]
+    enum Command {
+        Remote {
+            #[clap(subcommand)]
+            command: RemoteCommand,
+        },
+        Status,
+        #[clap(external_subcommand)]
+        External(Vec<String>),
+    }
+
+    #[derive(Subcommand, Debug)]
+    enum RemoteCommand {
+        Add(RemoteAdd),
+        Remove,
+    }
+
+    #[derive(Args, Debug)]
+    struct RemoteAdd {
+        name: String,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "remote", "add", "origin"]).unwrap();
+    assert_eq!(flags.subcommand_path(), vec!["remote", "add"]);
+
+    let flags = Application::try_parse_from(vec!["app_name", "remote", "remove"]).unwrap();
+    assert_eq!(flags.subcommand_path(), vec!["remote", "remove"]);
+
+    let flags = Application::try_parse_from(vec!["app_name", "status"]).unwrap();
+    assert_eq!(flags.subcommand_path(), vec!["status"]);
+
+    let flags = Application::try_parse_from(vec!["app_name", "plugin", "--arg"]).unwrap();
+    assert_eq!(flags.subcommand_path(), vec!["plugin"]);
+
+    let flags = Application::try_parse_from(vec!["app_name", "--verbose"]).unwrap();
+    assert!(flags.subcommand_path().is_empty());
+}
+
+#[test]
+fn test_recursive_subcommand() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        Exec {
+            #[clap(long)]
+            env: Option<String>,
+            #[clap(subcommand)]
+            command: Option<Box<Command>>,
+        },
+        Wrap {
+            #[clap(subcommand)]
+            command: Box<Command>,
+        },
+        Run,
+    }
+
+    let mut flags = Application::try_parse_from(vec![
+        "app_name", "exec", "--env", "a", "wrap", "exec", "run",
+    ])
+    .unwrap();
+
+    assert_eq!(
+        flags.command,
+        Command::Exec {
+            env: Some("a".to_string()),
+            command: Some(Box::new(Command::Wrap {
+                command: Box::new(Command::Exec {
+                    env: None,
+                    command: Some(Box::new(Command::Run)),
+                }),
+            })),
+        }
+    );
+    assert_eq!(
+        flags.command.subcommand_path(),
+        vec!["exec", "wrap", "exec", "run"]
+    );
+
+    flags
+        .try_update_from(vec!["app_name", "exec", "--env", "b"])
+        .unwrap();
+
+    assert_eq!(
+        flags.command,
+        Command::Exec {
+            env: Some("b".to_string()),
+            command: Some(Box::new(Command::Wrap {
+                command: Box::new(Command::Exec {
+                    env: None,
+                    command: Some(Box::new(Command::Run)),
+                }),
+            })),
+        }
+    );
+
+    assert!(Application::try_parse_from(vec!["app_name", "wrap"]).is_err());
+}
+
+#[test]
+fn test_recursive_subcommand_tree() {
+    use clap_derive_darling::Subcommand as _;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum Command {
+        Exec {
+            #[clap(subcommand)]
+            command: Option<Box<Command>>,
+        },
+        Nice {
+            #[clap(long)]
+            level: Option<u8>,
+            #[clap(subcommand)]
+            command: Box<Command>,
+        },
+        Time(TimeArgs),
+        Run,
+    }
+
+    #[derive(Args, Debug, PartialEq)]
+    struct TimeArgs {
+        #[clap(subcommand)]
+        command: Box<Command>,
+    }
+
+    let mut args = vec!["app_name"];
+    for _ in 0..10 {
+        args.extend(["exec", "nice", "--level", "1", "time"]);
+    }
+    args.push("run");
+
+    let flags = Application::try_parse_from(args.clone()).unwrap();
+    assert_eq!(
+        flags.command.subcommand_path(),
+        args[1..]
+            .iter()
+            .copied()
+            .filter(|arg| !["--level", "1"].contains(arg))
+            .collect::<Vec<_>>()
+    );
+
+    let flags = Application::try_parse_from(vec!["app_name", "exec"]).unwrap();
+    assert_eq!(flags.command, Command::Exec { command: None });
+
+    let err = Application::try_parse_from(vec!["app_name", "exec", "time", "exec", "bogus"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("'bogus'"));
+
+    assert!(Application::try_parse_from(vec!["app_name", "exec", "time"]).is_err());
+
+    let mut flags = Application::try_parse_from(vec![
+        "app_name", "nice", "--level", "1", "nice", "--level", "2", "run",
+    ])
+    .unwrap();
+    flags
+        .try_update_from(vec!["app_name", "nice", "nice", "--level", "3", "run"])
+        .unwrap();
+    assert_eq!(
+        flags.command,
+        Command::Nice {
+            level: None,
+            command: Box::new(Command::Nice {
+                level: Some(3),
+                command: Box::new(Command::Run),
+            }),
+        }
+    );
+
+    // The subcommands of a boxed field are only built once given, so help doesn't list them
+    let err = Application::try_parse_from(vec!["app_name", "exec", "--help"]).unwrap_err();
+    assert_eq!(err.kind(), clap::ErrorKind::DisplayHelp);
+    let help = err.to_string();
+    assert!(help.contains("[SUBCOMMAND]"));
+    assert!(!help.contains("SUBCOMMANDS:"));
+}
+
+#[test]
+fn test_shared_subcommand_args() {
+    use clap_derive_darling::WithArgs;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: WithArgs<Command>,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    #[clap(args = "CommonArgs")]
+    enum Command {
+        Build {
+            #[clap(long)]
+            release: bool,
+        },
+        Test(TestArgs),
+        Clean,
+        #[clap(external_subcommand)]
+        External(Vec<String>),
+    }
+
+    #[derive(Args, Debug, PartialEq)]
+    struct TestArgs {
+        filter: Option<String>,
+    }
+
+    #[derive(Args, Debug, PartialEq)]
+    struct CommonArgs {
+        #[clap(long, short)]
+        verbose: bool,
+        #[clap(long, default_value = "debug")]
+        profile: String,
+    }
+
+    let mut flags =
+        Application::try_parse_from(vec!["app_name", "build", "--release", "-v"]).unwrap();
+    assert_eq!(flags.command.subcommand, Command::Build { release: true });
+    assert!(flags.command.args.as_ref().unwrap().verbose);
+    assert_eq!(flags.command.args.as_ref().unwrap().profile, "debug");
+
+    flags
+        .try_update_from(vec!["app_name", "build", "--profile", "bench"])
+        .unwrap();
+    assert_eq!(*flags.command, Command::Build { release: false });
+    assert_eq!(flags.command.args.as_ref().unwrap().profile, "bench");
+
+    let flags = Application::try_parse_from(vec!["app_name", "test", "--verbose", "unit"]).unwrap();
+    assert_eq!(
+        flags.command.subcommand,
+        Command::Test(TestArgs {
+            filter: Some("unit".to_string())
+        })
+    );
+    assert!(flags.command.args.as_ref().unwrap().verbose);
+
+    let flags =
+        Application::try_parse_from(vec!["app_name", "clean", "--profile", "release"]).unwrap();
+    assert_eq!(flags.command.subcommand, Command::Clean);
+    assert_eq!(flags.command.args.as_ref().unwrap().profile, "release");
+
+    let flags = Application::try_parse_from(vec!["app_name", "plugin", "-v"]).unwrap();
+    assert_eq!(
+        flags.command.subcommand,
+        Command::External(vec!["plugin".to_string(), "-v".to_string()])
+    );
+    assert_eq!(flags.command.args, None);
+}
+
+#[test]
+fn test_shared_subcommand_args_prefix() {
+    use clap_derive_darling::WithArgs;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(flatten = "db")]
+        db: Context,
+    }
+
+    #[derive(Args, Debug)]
+    #[clap(flatten("db"))]
+    struct Context {
+        #[clap(subcommand)]
+        command: WithArgs<Command>,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    #[clap(args = "CommonArgs")]
+    enum Command {
+        Connect,
+        #[clap(prefix = "reset")]
+        Reset,
+        #[clap(external_subcommand)]
+        External(Vec<String>),
+    }
+
+    #[derive(Args, Debug, PartialEq)]
+    #[clap(flatten("db"))]
+    struct CommonArgs {
+        #[clap(long)]
+        user: String,
+    }
+
+    let mut flags =
+        Application::try_parse_from(vec!["app_name", "connect", "--db-user", "a"]).unwrap();
+    assert_eq!(
+        flags.db.command.args,
+        Some(CommonArgs {
+            user: "a".to_string()
+        })
+    );
+    assert!(Application::try_parse_from(vec!["app_name", "connect", "--user", "a"]).is_err());
+
+    flags
+        .try_update_from(vec!["app_name", "reset", "--user", "b"])
+        .unwrap();
+    assert_eq!(*flags.db.command, Command::Reset);
+    assert_eq!(
+        flags.db.command.args,
+        Some(CommonArgs {
+            user: "b".to_string()
+        })
+    );
+
+    flags.try_update_from(vec!["app_name", "plugin"]).unwrap();
+    assert_eq!(
+        *flags.db.command,
+        Command::External(vec!["plugin".to_string()])
+    );
+    assert_eq!(flags.db.command.args, None);
+
+    let flags = Application::try_parse_from(vec!["app_name", "plugin", "--user"]).unwrap();
+    assert_eq!(flags.db.command.args, None);
+}
+
+#[test]
+fn test_subcommand_env_scope() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    #[clap(env_scope)]
+    enum Command {
+        EnvScopeDeploy {
+            #[clap(long, env)]
+            region: String,
+        },
+        EnvScopeDestroy {
+            #[clap(long, env)]
+            region: String,
+            #[clap(long, env = "ENV_SCOPE_FORCE")]
+            force: Option<String>,
+        },
+    }
+
+    std::env::set_var("ENV_SCOPE_DEPLOY_REGION", "eu-west-1");
+    std::env::set_var("ENV_SCOPE_DESTROY_REGION", "us-east-1");
+    std::env::set_var("ENV_SCOPE_FORCE", "yes");
+
+    let flags = Application::try_parse_from(vec!["app_name", "env-scope-deploy"]).unwrap();
+    assert_eq!(
+        flags.command,
+        Command::EnvScopeDeploy {
+            region: "eu-west-1".to_string()
+        }
+    );
+
+    let flags = Application::try_parse_from(vec!["app_name", "env-scope-destroy"]).unwrap();
+    assert_eq!(
+        flags.command,
+        Command::EnvScopeDestroy {
+            region: "us-east-1".to_string(),
+            force: Some("yes".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_arg_enum_variant_attributes() {
+    use clap::IntoApp;
+    use clap_derive_darling::ArgEnum as _;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum)]
+        level: Level,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    enum Level {
+        #[clap(name = "dbg", alias = "debug")]
+        Debug,
+        #[clap(aliases("information", "notice"))]
+        Info,
+        #[clap(hide)]
+        Trace,
+        #[clap(skip)]
+        #[allow(dead_code)]
+        Custom(u8),
+    }
+
+    for (input, level) in [
+        ("dbg", Level::Debug),
+        ("debug", Level::Debug),
+        ("info", Level::Info),
+        ("notice", Level::Info),
+        ("trace", Level::Trace),
+    ] {
+        let flags = Application::try_parse_from(vec!["app_name", "--level", input]).unwrap();
+        assert_eq!(flags.level, level);
+    }
+
+    assert!(Application::try_parse_from(vec!["app_name", "--level", "custom"]).is_err());
+
+    assert_eq!(
+        Level::value_variants(),
+        &[Level::Debug, Level::Info, Level::Trace]
+    );
+    assert!(Level::Custom(1).to_possible_value().is_none());
+
+    let mut help = Vec::new();
+    Application::into_app().write_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("dbg"));
+    assert!(!help.contains("trace"));
+}
+
+#[test]
+fn test_arg_enum_doc_comments() {
+    use clap::IntoApp;
+    use clap_derive_darling::ArgEnum as _;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        /// Output compression
+        #[clap(long, arg_enum)]
+        compression: Compression,
+        /// Fallback compression
+        ///
+        /// Used when the output doesn't support the primary one.
+        #[clap(long, arg_enum)]
+        fallback: Compression,
+    }
+
+    /// Compression applied to the output
+    ///
+    /// Higher levels are slower.
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    enum Compression {
+        /// Fast but lossy
+        ///
+        /// Not shown, possible values only have a single help line.
+        Fast,
+        #[clap(help = "Slow but lossless")]
+        /// Overridden by the help attribute
+        Best,
+        None,
+    }
+
+    let help_of = |v: Compression| {
+        v.to_possible_value()
+            .unwrap()
+            .get_help()
+            .map(str::to_string)
+    };
+    assert_eq!(
+        help_of(Compression::Fast).as_deref(),
+        Some("Fast but lossy")
+    );
+    assert_eq!(
+        help_of(Compression::Best).as_deref(),
+        Some("Slow but lossless")
+    );
+    assert_eq!(help_of(Compression::None), None);
+
+    assert_eq!(
+        Compression::long_help(),
+        Some("Compression applied to the output\n\nHigher levels are slower.")
+    );
+
+    let mut help = Vec::new();
+    Application::into_app().write_long_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("Output compression"));
+    assert!(help.contains("Higher levels are slower."));
+    assert!(help.contains("Used when the output doesn't support the primary one."));
+
+    let mut help = Vec::new();
+    Application::into_app().write_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("Output compression"));
+    assert!(!help.contains("Higher levels are slower."));
+}
+
+#[test]
+fn test_arg_enum_ignore_case() {
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum, ignore_case)]
+        level: Option<Level>,
+        #[clap(long, arg_enum)]
+        strict_level: Option<Level>,
+        #[clap(long, arg_enum)]
+        colour: Option<Colour>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    enum Level {
+        #[clap(alias = "warning")]
+        Warn,
+        Debug,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    #[clap(ignore_case)]
+    enum Colour {
+        Red,
+        Green,
+    }
+
+    for input in ["debug", "DEBUG", "Debug"] {
+        let flags = Application::try_parse_from(vec!["app_name", "--level", input]).unwrap();
+        assert_eq!(flags.level, Some(Level::Debug));
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "--level", "WARNING"]).unwrap();
+    assert_eq!(flags.level, Some(Level::Warn));
+
+    assert!(Application::try_parse_from(vec!["app_name", "--strict-level", "DEBUG"]).is_err());
+
+    let flags = Application::try_parse_from(vec!["app_name", "--colour", "GREEN"]).unwrap();
+    assert_eq!(flags.colour, Some(Colour::Green));
+
+    let mut flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+    flags
+        .try_update_from(vec!["app_name", "--level", "WARN"])
+        .unwrap();
+    assert_eq!(flags.level, Some(Level::Warn));
+}
+
+#[test]
+fn test_arg_enum_from_str_display() {
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    #[clap(from_str, display, variant_names, rename_all = "kebab-case")]
+    enum Format {
+        #[clap(alias = "yml")]
+        Yaml,
+        JsonLines,
+        #[clap(name = "text")]
+        PlainText,
+    }
+
+    assert_eq!(Format::VARIANT_NAMES, &["yaml", "json-lines", "text"]);
+
+    for (name, format) in [
+        ("yaml", Format::Yaml),
+        ("json-lines", Format::JsonLines),
+        ("text", Format::PlainText),
+    ] {
+        assert_eq!(format.to_string(), name);
+        assert_eq!(name.parse::<Format>().unwrap(), format);
+    }
+
+    assert_eq!("yml".parse::<Format>().unwrap(), Format::Yaml);
+    assert!("Yaml".parse::<Format>().is_err());
+    assert!("plain-text".parse::<Format>().is_err());
+}
+
+#[test]
+fn test_arg_enum_other() {
+    use clap::IntoApp;
+    use clap_derive_darling::ArgEnum as _;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum)]
+        shell: Shell,
+        #[clap(long, arg_enum)]
+        shells: Vec<Shell>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    #[clap(display, variant_names)]
+    enum Shell {
+        Bash,
+        Zsh,
+        #[clap(other)]
+        Other(String),
+    }
+
+    let flags = Application::try_parse_from(vec![
+        "app_name", "--shell", "zsh", "--shells", "fish", "--shells", "bash",
+    ])
+    .unwrap();
+    assert_eq!(flags.shell, Shell::Zsh);
+    assert_eq!(
+        flags.shells,
+        vec![Shell::Other("fish".to_string()), Shell::Bash]
+    );
+
+    let flags = Application::try_parse_from(vec!["app_name", "--shell", "nu"]).unwrap();
+    assert_eq!(flags.shell, Shell::Other("nu".to_string()));
+    assert_eq!(flags.shell.to_string(), "nu");
+
+    assert_eq!(Shell::value_variants(), &[Shell::Bash, Shell::Zsh]);
+    assert_eq!(Shell::VARIANT_NAMES, &["bash", "zsh"]);
+    assert!(Shell::Other("nu".to_string()).to_possible_value().is_none());
+
+    Application::into_app().debug_assert();
+
+    let mut help = Vec::new();
+    Application::into_app().write_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("[possible values: bash, zsh]"));
+}
+
+#[test]
+fn test_arg_enum_multiple_values() {
+    use std::collections::{BTreeSet, HashSet};
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum, value_delimiter = ',')]
+        features: Vec<Feature>,
+        #[clap(long, arg_enum, value_delimiter = ',', pseudo_values)]
+        enabled: BTreeSet<Feature>,
+        #[clap(long, arg_enum, pseudo_values)]
+        disabled: Option<HashSet<Feature>>,
+    }
+
+    #[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum Feature {
+        Tls,
+        Gzip,
+        Brotli,
+    }
+
+    let flags = Application::try_parse_from(vec![
+        "app_name",
+        "--features",
+        "tls,gzip",
+        "--features",
+        "tls",
+        "--enabled",
+        "brotli,tls,brotli",
+    ])
+    .unwrap();
+    assert_eq!(
+        flags.features,
+        vec![Feature::Tls, Feature::Gzip, Feature::Tls]
+    );
+    assert_eq!(
+        flags.enabled,
+        BTreeSet::from([Feature::Tls, Feature::Brotli])
+    );
+    assert_eq!(flags.disabled, None);
+
+    let flags =
+        Application::try_parse_from(vec!["app_name", "--enabled", "all", "--disabled", "none"])
+            .unwrap();
+    assert_eq!(
+        flags.enabled,
+        BTreeSet::from([Feature::Tls, Feature::Gzip, Feature::Brotli])
+    );
+    assert_eq!(flags.disabled, Some(HashSet::new()));
+
+    let flags = Application::try_parse_from(vec!["app_name", "--disabled", "gzip"]).unwrap();
+    assert_eq!(flags.disabled, Some(HashSet::from([Feature::Gzip])));
+
+    assert!(Application::try_parse_from(vec!["app_name", "--enabled", "none,tls"]).is_err());
+    assert!(Application::try_parse_from(vec![
+        "app_name",
+        "--disabled",
+        "gzip",
+        "--disabled",
+        "none"
+    ])
+    .is_err());
+
+    assert!(Application::try_parse_from(vec!["app_name", "--features", "tls,zstd"]).is_err());
+    assert!(Application::try_parse_from(vec!["app_name", "--features", "all"]).is_err());
+
+    let mut flags = Application::try_parse_from(vec!["app_name"]).unwrap();
+    flags
+        .try_update_from(vec!["app_name", "--enabled", "gzip,tls"])
+        .unwrap();
+    assert_eq!(flags.enabled, BTreeSet::from([Feature::Tls, Feature::Gzip]));
+}
+
+#[test]
+#[should_panic(expected = "variant `all` collides with the pseudo value `all`")]
+fn test_arg_enum_pseudo_values_collision() {
+    use clap::IntoApp;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum, value_delimiter = ',', pseudo_values)]
+        targets: Vec<Target>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug)]
+    enum Target {
+        Host,
+        All,
+    }
+
+    Application::into_app();
+}
+
+#[test]
+fn test_arg_enum_infer() {
+    use clap::IntoApp;
+    use clap_derive_darling::ArgEnum as _;
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum)]
+        level: Option<Level>,
+        #[clap(long, arg_enum)]
+        mode: Option<Mode>,
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    #[clap(infer, ignore_case)]
+    enum Level {
+        Debug,
+        #[clap(alias = "devel")]
+        Development,
+        Info,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Mode {
+        Fast,
+        Full,
+    }
+
+    impl clap_derive_darling::ArgEnum for Mode {
+        fn value_variants<'a>() -> &'a [Self] {
+            &[Self::Fast, Self::Full]
+        }
+        fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
+            Some(clap::PossibleValue::new(match self {
+                Self::Fast => "fast",
+                Self::Full => "full",
+            }))
+        }
+        fn infer() -> bool {
+            true
+        }
+    }
+
+    for (input, level) in [
+        ("debug", Level::Debug),
+        ("deb", Level::Debug),
+        ("DEVEL", Level::Development),
+        ("i", Level::Info),
+    ] {
+        let flags = Application::try_parse_from(vec!["app_name", "--level", input]).unwrap();
+        assert_eq!(flags.level, Some(level));
+    }
+
+    let err = Application::try_parse_from(vec!["app_name", "--level", "de"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Ambiguous variant: de, could be one of: debug, development"));
+
+    assert!(Application::try_parse_from(vec!["app_name", "--level", "warn"]).is_err());
+    assert!(Level::from_str("", true).is_err());
+
+    let flags = Application::try_parse_from(vec!["app_name", "--mode", "fa"]).unwrap();
+    assert_eq!(flags.mode, Some(Mode::Fast));
+    assert!(Application::try_parse_from(vec!["app_name", "--mode", "f"]).is_err());
+
+    let mut help = Vec::new();
+    Application::into_app().write_help(&mut help).unwrap();
+    let help = String::from_utf8(help).unwrap();
+
+    assert!(help.contains("[possible values: debug, development, info]"));
+    assert!(help.contains("[possible values: fast, full]"));
+}
+
+#[test]
+fn test_arg_enum_remote() {
+    use clap_derive_darling::ArgEnum as _;
+
+    mod other_crate {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum Level {
+            Error,
+            Warn,
+            Info,
+        }
+    }
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    #[clap(remote = "other_crate::Level")]
+    enum LevelDef {
+        Error,
+        #[clap(alias = "warning")]
+        Warn,
+        Info,
+    }
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum, with = "LevelDef")]
+        level: other_crate::Level,
+        #[clap(
+            long,
+            arg_enum,
+            with = "LevelDef",
+            value_delimiter = ',',
+            pseudo_values
+        )]
+        levels: Vec<other_crate::Level>,
+    }
+
+    let flags = Application::try_parse_from(vec![
+        "app_name",
+        "--level",
+        "warning",
+        "--levels",
+        "info,error",
+    ])
+    .unwrap();
+    assert_eq!(flags.level, other_crate::Level::Warn);
+    assert_eq!(
+        flags.levels,
+        vec![other_crate::Level::Info, other_crate::Level::Error]
+    );
+
+    let flags = Application::try_parse_from(vec!["app_name", "--level", "info", "--levels", "all"])
+        .unwrap();
+    assert_eq!(
+        flags.levels,
+        vec![
+            other_crate::Level::Error,
+            other_crate::Level::Warn,
+            other_crate::Level::Info
+        ]
+    );
+
+    assert!(Application::try_parse_from(vec!["app_name", "--level", "debug"]).is_err());
+
+    assert_eq!(
+        LevelDef::try_from(other_crate::Level::Error),
+        Ok(LevelDef::Error)
+    );
+    assert_eq!(
+        other_crate::Level::from(LevelDef::Info),
+        other_crate::Level::Info
+    );
+    assert_eq!(
+        LevelDef::try_from(other_crate::Level::Warn)
+            .unwrap()
+            .to_possible_value()
+            .unwrap()
+            .get_name(),
+        "warn"
+    );
+}
+
+#[test]
+fn test_arg_enum_remote_non_exhaustive() {
+    use std::sync::atomic::Ordering;
+
+    #[derive(ArgEnum, Clone, Debug, PartialEq)]
+    #[clap(remote = "Ordering")]
+    enum OrderingDef {
+        Relaxed,
+        SeqCst,
+    }
+
+    #[derive(Parser, Debug)]
+    struct Application {
+        #[clap(long, arg_enum, with = "OrderingDef")]
+        ordering: Ordering,
+    }
+
+    let flags = Application::try_parse_from(vec!["app_name", "--ordering", "seq-cst"]).unwrap();
+    assert_eq!(flags.ordering, Ordering::SeqCst);
+
+    assert_eq!(
+        OrderingDef::try_from(Ordering::Relaxed),
+        Ok(OrderingDef::Relaxed)
+    );
+    assert_eq!(
+        OrderingDef::try_from(Ordering::Acquire),
+        Err(Ordering::Acquire)
+    );
 }